@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use tracing::info;
+
+use crate::alert::alert_dto::CreateAlertRequest;
+use crate::alert::alert_model::{AlertCondition, AlertRule, AlertScope};
+use crate::cache::unix_now;
+use crate::config::settings;
+use crate::error::{ApiResult, ScraperError};
+use crate::marketplace::marketplace_controller::ApiResponseError;
+use crate::marketplace::marketplace_dto::ApiResponse;
+use crate::outbound_url::check_outbound_url;
+use crate::state::AppState;
+
+/// HTTP handler adding a price alert rule
+#[utoipa::path(
+    post,
+    path = "/api/scraper/alerts",
+    request_body = CreateAlertRequest,
+    responses(
+        (status = 201, description = "Rule created, evaluated on every run of the watches it covers", body = ApiResponse<AlertRule>),
+        (status = 400, description = "Invalid product URL, query or condition, an invalid or internal webhook, or no `alerts.webhook_secret` configured", body = inline(ApiResponseError)),
+        (status = 500, description = "Rule could not be stored", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn create_alert_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CreateAlertRequest>,
+) -> ApiResult<AlertRule> {
+    info!("Received alert request: {:?}, {:?}", body.scope, body.condition);
+
+    validate(&state, &body).await.map_err(ScraperError::InvalidRequest)?;
+
+    let rule = AlertRule {
+        id: Uuid::new_v4().to_string(),
+        scope: body.scope,
+        condition: body.condition,
+        webhook_url: body.webhook_url,
+        created_at: unix_now(),
+        last_fired_at: None,
+    };
+    state
+        .watches
+        .save_alert(&rule)
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to store alert: {e}")))?;
+    info!("Alert {} created", rule.id);
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(rule, 1))))
+}
+
+/// Reject rules that could never fire or be delivered
+async fn validate(state: &AppState, body: &CreateAlertRequest) -> Result<(), String> {
+    let alerts = &settings().alerts;
+    if alerts.webhook_secret.is_empty() {
+        return Err("Alerts are disabled until alerts.webhook_secret is configured".to_string());
+    }
+
+    match &body.scope {
+        AlertScope::Product { url } => {
+            state.marketplaces.find_by_url(url).map_err(|e| e.to_string())?;
+        }
+        AlertScope::Search { query } if query.trim().is_empty() => {
+            return Err("search.query must not be empty".to_string());
+        }
+        AlertScope::Search { .. } => {}
+    }
+
+    match body.condition {
+        AlertCondition::PriceBelow { price_idr: 0 } => return Err("price_idr must be positive".to_string()),
+        AlertCondition::DropPercent { percent } if !(percent > 0.0 && percent <= 100.0) => {
+            return Err("percent must be greater than 0 and at most 100".to_string());
+        }
+        _ => {}
+    }
+
+    match &body.webhook_url {
+        // Signed with the deployment's secret, so only sent where the server may send it
+        Some(url) => check_outbound_url(url, &alerts.webhook_allowed_hosts)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("webhook_url: {e}")),
+        None if alerts.webhook_url.is_empty() => {
+            Err("webhook_url is required when alerts.webhook_url is not configured".to_string())
+        }
+        None => Ok(()),
+    }
+}
+
+/// HTTP handler listing every alert rule
+#[utoipa::path(
+    get,
+    path = "/api/scraper/alerts",
+    responses(
+        (status = 200, description = "Every alert rule, oldest first", body = ApiResponse<Vec<AlertRule>>),
+        (status = 500, description = "Watch store error", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn list_alerts_handler(State(state): State<AppState>) -> ApiResult<Vec<AlertRule>> {
+    let rules = state
+        .watches
+        .alerts()
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to read alerts: {e}")))?;
+    let count = rules.len();
+    Ok((StatusCode::OK, Json(ApiResponse::success(rules, count))))
+}
+
+/// HTTP handler removing an alert rule
+#[utoipa::path(
+    delete,
+    path = "/api/scraper/alerts/{id}",
+    params(("id" = String, Path, description = "Alert rule identifier")),
+    responses(
+        (status = 200, description = "Rule removed", body = ApiResponse<String>),
+        (status = 404, description = "Unknown rule", body = inline(ApiResponseError)),
+        (status = 500, description = "Watch store error", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn delete_alert_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<String> {
+    match state.watches.delete_alert(&id).await {
+        Ok(true) => {
+            info!("Alert {id} removed");
+            Ok((StatusCode::OK, Json(ApiResponse::success(id, 1))))
+        }
+        Ok(false) => Err(ScraperError::NotFound(format!("Unknown alert '{id}'"))),
+        Err(e) => Err(ScraperError::Internal(format!("Failed to remove alert {id}: {e}"))),
+    }
+}
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::alert::alert_model::{AlertCondition, AlertScope};
+
+/// Body of `POST /api/scraper/alerts`: either `product` or `search`, plus the condition
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAlertRequest {
+    #[serde(flatten)]
+    pub scope: AlertScope,
+    pub condition: AlertCondition,
+    /// Receiver of the alerts (default: `alerts.webhook_url`)
+    pub webhook_url: Option<String>,
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Which observations an alert rule looks at
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertScope {
+    /// One product, wherever a watch observes it
+    Product {
+        /// Product page URL; query string and fragment are ignored
+        url: String,
+    },
+    /// Every product found by search watches with this query (case and spacing ignored)
+    Search { query: String },
+}
+
+/// When an alert rule fires, comparing an observation with the previous one of the same product
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The price fell below `price_idr`; fires once when crossing, not on every run below it
+    PriceBelow { price_idr: u64 },
+    /// The price dropped at least `percent` percent since the previous observation
+    DropPercent { percent: f64 },
+}
+
+impl AlertCondition {
+    /// Whether a product priced `previous` at its last observation and `price` now triggers the condition
+    pub fn fires(&self, price: u64, previous: Option<u64>) -> bool {
+        match *self {
+            AlertCondition::PriceBelow { price_idr } => {
+                price < price_idr && previous.is_none_or(|previous| previous >= price_idr)
+            }
+            AlertCondition::DropPercent { percent } => {
+                drop_percent(price, previous).is_some_and(|dropped| dropped >= percent)
+            }
+        }
+    }
+}
+
+/// How much lower `price` is than `previous` in percent, negative when it rose
+pub fn drop_percent(price: u64, previous: Option<u64>) -> Option<f64> {
+    let previous = previous.filter(|previous| *previous > 0)?;
+    let dropped = (previous as f64 - price as f64) / previous as f64 * 100.0;
+    Some((dropped * 100.0).round() / 100.0)
+}
+
+/// A condition on a product's or search's prices, notified by webhook
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertRule {
+    pub id: String,
+    #[serde(flatten)]
+    pub scope: AlertScope,
+    pub condition: AlertCondition,
+    /// Receiver of the alerts; `alerts.webhook_url` when not set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub webhook_url: Option<String>,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_fired_at: Option<u64>,
+}
+
+/// JSON body POSTed to the webhook when a rule fires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AlertEvent {
+    /// Unique per alert, also sent as `X-Scraper-Delivery`; retries repeat it
+    pub id: String,
+    pub rule_id: String,
+    pub condition: AlertCondition,
+    pub product_url: String,
+    pub name: String,
+    /// Marketplace id, e.g. `tokopedia`
+    pub source: String,
+    pub price_idr: u64,
+    /// Price at the product's previous observation
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub previous_price_idr: Option<u64>,
+    /// Drop since the previous observation in percent
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub drop_percent: Option<f64>,
+    /// Watch whose run made the observation
+    pub watch_id: String,
+    /// Unix timestamps in seconds
+    pub observed_at: u64,
+    pub fired_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditions_fire_on_price_drops() {
+        let below = AlertCondition::PriceBelow { price_idr: 10_000_000 };
+        assert!(below.fires(9_500_000, Some(10_500_000)));
+        assert!(below.fires(9_500_000, None));
+        // Already below at the previous observation: no repeated alert
+        assert!(!below.fires(9_000_000, Some(9_500_000)));
+        assert!(!below.fires(10_000_000, Some(10_500_000)));
+
+        let dropped = AlertCondition::DropPercent { percent: 10.0 };
+        assert!(dropped.fires(9_000_000, Some(10_000_000)));
+        assert!(!dropped.fires(9_100_000, Some(10_000_000)));
+        assert!(!dropped.fires(9_000_000, None));
+        assert!(!dropped.fires(11_000_000, Some(10_000_000)));
+        assert_eq!(drop_percent(11_000_000, Some(10_000_000)), Some(-10.0));
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::alert::alert_model::{drop_percent, AlertEvent, AlertRule, AlertScope};
+use crate::alert::{sign, DELIVERY_HEADER, SIGNATURE_HEADER};
+use crate::cache::{normalize_query, unix_now};
+use crate::config::settings;
+use crate::outbound_url::{check_outbound_url, CheckedUrl};
+use crate::state::AppState;
+use crate::watch::product_key;
+use crate::watch::watch_model::{PriceObservation, Watch, WatchTarget};
+
+/// Fire the alert rules matching a watch run's observations, returning how many fired
+///
+/// Call it before the observations are recorded: each one is compared with the
+/// product's latest recorded observation. Webhooks are sent in the background.
+pub async fn check(state: &AppState, watch: &Watch, observations: &[PriceObservation]) -> Result<usize> {
+    let mut rules: Vec<AlertRule> = state.watches.alerts().await?;
+    rules.retain(|rule| match &rule.scope {
+        AlertScope::Product { .. } => true,
+        AlertScope::Search { query } => {
+            matches!(&watch.target, WatchTarget::Search(params) if normalize_query(&params.query) == normalize_query(query))
+        }
+    });
+    if rules.is_empty() {
+        return Ok(0);
+    }
+
+    let mut fired = 0;
+    let mut seen = HashSet::new();
+    // A price of 0 is one that could not be read and would fire every rule
+    for observation in observations.iter().filter(|observation| observation.price_idr > 0) {
+        let key = product_key(&observation.product_url);
+        // A search can list a product more than once
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        let matching: Vec<&mut AlertRule> = rules
+            .iter_mut()
+            .filter(|rule| match &rule.scope {
+                AlertScope::Product { url } => product_key(url) == key,
+                AlertScope::Search { .. } => true,
+            })
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+
+        let previous = state.watches.history(&key, None, 1).await?.pop().map(|last| last.price_idr);
+        for rule in matching {
+            if !rule.condition.fires(observation.price_idr, previous) {
+                continue;
+            }
+            let event = AlertEvent {
+                id: Uuid::new_v4().to_string(),
+                rule_id: rule.id.clone(),
+                condition: rule.condition.clone(),
+                product_url: observation.product_url.clone(),
+                name: observation.name.clone(),
+                source: observation.source.clone(),
+                price_idr: observation.price_idr,
+                previous_price_idr: previous,
+                drop_percent: drop_percent(observation.price_idr, previous),
+                watch_id: watch.id.clone(),
+                observed_at: observation.observed_at,
+                fired_at: unix_now(),
+            };
+            info!("Alert {} fired for {} at Rp{}", rule.id, observation.name, observation.price_idr);
+            fired += 1;
+
+            rule.last_fired_at = Some(event.fired_at);
+            if let Err(e) = state.watches.save_alert(rule).await {
+                warn!("Failed to save alert {}: {e}", rule.id);
+            }
+            spawn_delivery(rule, event);
+        }
+    }
+    Ok(fired)
+}
+
+fn spawn_delivery(rule: &AlertRule, event: AlertEvent) {
+    let alerts = &settings().alerts;
+    // Only rule URLs come from API callers; the configured one is trusted
+    let (url, allowed_hosts) = match &rule.webhook_url {
+        Some(url) => (url.clone(), Some(alerts.webhook_allowed_hosts.as_slice())),
+        None => (alerts.webhook_url.clone(), None),
+    };
+    if url.is_empty() || alerts.webhook_secret.is_empty() {
+        warn!("Alert {} not sent: no webhook URL or alerts.webhook_secret", rule.id);
+        return;
+    }
+    let span = info_span!("alert_delivery", alert_id = %event.id);
+    tokio::spawn(async move { deliver(&url, &alerts.webhook_secret, &event, allowed_hosts).await }.instrument(span));
+}
+
+/// POST a signed alert to `url`, retrying with a growing delay; `true` once delivered
+///
+/// With `allowed_hosts`, the URL is checked again right before sending, since
+/// the host may resolve elsewhere by now than when the rule was created.
+pub async fn deliver(url: &str, secret: &str, event: &AlertEvent, allowed_hosts: Option<&[String]>) -> bool {
+    let alerts = &settings().alerts;
+    let checked = match allowed_hosts {
+        Some(allowed_hosts) => match check_outbound_url(url, allowed_hosts).await {
+            Ok(checked) => checked,
+            Err(e) => {
+                error!("Alert {} refused: {e}", event.id);
+                return false;
+            }
+        },
+        None => CheckedUrl::default(),
+    };
+    // Connecting to the checked addresses and not following redirects keeps
+    // the signed alert on the host it was checked against
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(alerts.webhook_timeout_secs))
+        .redirect(reqwest::redirect::Policy::none());
+    let client = match checked.pin(builder).build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Alert {} not sent: failed to create HTTP client: {e}", event.id);
+            return false;
+        }
+    };
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Alert {} not sent: failed to encode it: {e}", event.id);
+            return false;
+        }
+    };
+    let signature = sign(secret, &body);
+
+    for attempt in 1..=alerts.webhook_attempts {
+        let response = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(DELIVERY_HEADER, &event.id)
+            .body(body.clone())
+            .send()
+            .await;
+        let error = match response {
+            Ok(response) if response.status().is_success() => {
+                info!("Alert {} delivered to {url}", event.id);
+                return true;
+            }
+            Ok(response) => format!("HTTP {}", response.status()),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt < alerts.webhook_attempts {
+            let delay = Duration::from_secs(1 << (attempt - 1).min(6));
+            warn!("Alert {} attempt {attempt} failed ({error}), retrying in {delay:?}", event.id);
+            tokio::time::sleep(delay).await;
+        } else {
+            error!("Alert {} to {url} failed after {attempt} attempt(s): {error}", event.id);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::alert_model::AlertCondition;
+    use crate::cache::MemoryCache;
+    use crate::marketplace::marketplace_registry::MarketplaceRegistry;
+    use crate::page_source::{FixturePageSource, PageSource, PageSources};
+    use crate::watch::SqliteWatchStore;
+    use axum::{body::Bytes, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    fn event() -> AlertEvent {
+        AlertEvent {
+            id: "event-1".to_string(),
+            rule_id: "rule-1".to_string(),
+            condition: AlertCondition::PriceBelow { price_idr: 14_000_000 },
+            product_url: "https://www.tokopedia.com/shop/iphone".to_string(),
+            name: "iPhone 15".to_string(),
+            source: "tokopedia".to_string(),
+            price_idr: 13_499_000,
+            previous_price_idr: Some(14_999_000),
+            drop_percent: drop_percent(13_499_000, Some(14_999_000)),
+            watch_id: "watch-1".to_string(),
+            observed_at: 1,
+            fired_at: 2,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rules_fire_for_matching_products_and_searches() {
+        let pages: Arc<dyn PageSource> = Arc::new(FixturePageSource::new("fixtures"));
+        let state = AppState::new(
+            PageSources { browser: pages.clone(), http: pages },
+            MarketplaceRegistry::default(),
+            Arc::new(MemoryCache::new(10)),
+        )
+        .with_watch_store(Arc::new(SqliteWatchStore::open_in_memory().unwrap()));
+
+        let observation = |url: &str, price_idr| PriceObservation {
+            product_url: url.to_string(),
+            source: "tokopedia".to_string(),
+            name: "iPhone 15".to_string(),
+            price_idr,
+            original_price_idr: None,
+            discount_percent: None,
+            sold_min: None,
+            rating: None,
+            review_count: None,
+            stock: None,
+            watch_id: Some("watch-1".to_string()),
+            observed_at: 1,
+        };
+        let rule = |id: &str, scope, condition| AlertRule {
+            id: id.to_string(),
+            scope,
+            condition,
+            webhook_url: None,
+            created_at: 0,
+            last_fired_at: None,
+        };
+        let iphone = "https://www.tokopedia.com/shop/iphone";
+        let product = AlertScope::Product { url: format!("{iphone}?src=topads") };
+        state.watches.save_alert(&rule("below", product, AlertCondition::PriceBelow { price_idr: 14_000_000 })).await.unwrap();
+        let other = AlertScope::Product { url: "https://www.tokopedia.com/shop/case".to_string() };
+        state.watches.save_alert(&rule("other", other, AlertCondition::PriceBelow { price_idr: 14_000_000 })).await.unwrap();
+        let search = AlertScope::Search { query: "IPhone  15".to_string() };
+        state.watches.save_alert(&rule("search", search, AlertCondition::DropPercent { percent: 5.0 })).await.unwrap();
+        state.watches.record(&[observation(iphone, 14_500_000)]).await.unwrap();
+
+        let watch = Watch {
+            id: "watch-1".to_string(),
+            target: WatchTarget::Search(serde_json::from_value(serde_json::json!({ "query": "iphone 15" })).unwrap()),
+            schedule: "0 */6 * * *".to_string(),
+            created_at: 0,
+            last_run_at: None,
+            next_run_at: Some(0),
+            last_error: None,
+        };
+        let unpriced = observation("https://www.tokopedia.com/shop/case", 0);
+        assert_eq!(check(&state, &watch, &[unpriced]).await.unwrap(), 0);
+
+        let fired = check(&state, &watch, &[observation(iphone, 13_500_000), observation(iphone, 13_500_000)]).await;
+        assert_eq!(fired.unwrap(), 2);
+
+        let rules = state.watches.alerts().await.unwrap();
+        let fired: Vec<&str> = rules.iter().filter(|rule| rule.last_fired_at.is_some()).map(|rule| rule.id.as_str()).collect();
+        assert_eq!(fired, ["below", "search"]);
+    }
+
+    #[tokio::test]
+    async fn test_alert_is_signed_and_retried() {
+        // Receiver failing the first request, then checking the signature like a real one would
+        let received = Arc::new(Mutex::new(Vec::<(Option<String>, Bytes)>::new()));
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/alerts",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                let mut received = sink.lock().unwrap();
+                let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok()).map(String::from);
+                received.push((signature, body));
+                if received.len() == 1 { StatusCode::INTERNAL_SERVER_ERROR } else { StatusCode::OK }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let allowed = ["127.0.0.1".to_string()];
+        assert!(deliver(&url, "s3cret", &event(), Some(&allowed)).await);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        let (signature, body) = &received[1];
+        assert_eq!(signature.as_deref(), Some(sign("s3cret", body).as_str()));
+        assert_ne!(signature.as_deref(), Some(sign("other", body).as_str()));
+        let sent: AlertEvent = serde_json::from_slice(body).unwrap();
+        assert_eq!(sent, event());
+        assert_eq!(sent.drop_percent, Some(10.0));
+    }
+
+    #[tokio::test]
+    async fn test_rule_webhook_to_internal_address_is_refused_when_sent() {
+        assert!(!deliver("http://127.0.0.1:9/alerts", "s3cret", &event(), Some(&[])).await);
+    }
+}
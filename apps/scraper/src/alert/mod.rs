@@ -0,0 +1,59 @@
+pub mod alert_controller;
+pub mod alert_dto;
+pub mod alert_model;
+pub mod alert_service;
+
+use anyhow::{bail, Result};
+use axum::{routing::{delete, get}, Router};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use url::Url;
+
+use crate::state::AppState;
+
+/// Header carrying the HMAC-SHA256 of the request body, `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-Scraper-Signature";
+/// Header carrying the alert's id, the same on every retry
+pub const DELIVERY_HEADER: &str = "X-Scraper-Delivery";
+
+/// Signature of a webhook body as sent in [`SIGNATURE_HEADER`]
+///
+/// Receivers compute the same over the raw body with the shared secret and
+/// compare, rejecting requests that don't match.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Reject webhook URLs that are not http(s)
+pub fn check_webhook_url(url: &str) -> Result<()> {
+    let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!("Invalid webhook URL '{url}': {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("Webhook URL must be an http or https URL, got '{url}'");
+    }
+    Ok(())
+}
+
+/// Create router for the alert rule endpoints
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/scraper/alerts",
+            get(alert_controller::list_alerts_handler).post(alert_controller::create_alert_handler),
+        )
+        .route("/api/scraper/alerts/:id", delete(alert_controller::delete_alert_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_is_hmac_sha256_of_body() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
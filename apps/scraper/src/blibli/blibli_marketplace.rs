@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+
+use crate::blibli::BlibliParser;
+use crate::block_detection::PageSignatures;
+use crate::config::*;
+use crate::marketplace::marketplace_model::{Product, ProductDetail, SearchFilters, SortOrder};
+use crate::marketplace::Marketplace;
+use crate::page_source::RenderOptions;
+
+/// Script that counts the product cards currently rendered on a search page
+const PRODUCT_COUNT_SCRIPT: &str = r#"
+    (function() {
+        const productCards = document.querySelectorAll('a.elf-product-card');
+        return productCards.length;
+    })();
+"#;
+
+/// Script that reports whether a product page has rendered its title
+const PRODUCT_PAGE_READY_SCRIPT: &str = r#"
+    (function() {
+        return document.querySelector('.product-name, script[type="application/ld+json"]') ? 1 : 0;
+    })();
+"#;
+
+/// Blibli's own empty search and error pages
+const PAGE_SIGNATURES: PageSignatures = PageSignatures {
+    empty: &["produk yang kamu cari tidak ditemukan", "class=\"empty-state"],
+    error: &["terjadi kesalahan", "halaman sedang dalam perbaikan"],
+    search_page: &["class=\"product-list"],
+    ..PageSignatures::NONE
+};
+
+pub struct BlibliMarketplace;
+
+#[async_trait]
+impl Marketplace for BlibliMarketplace {
+    fn id(&self) -> &'static str {
+        "blibli"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Blibli"
+    }
+
+    fn domain(&self) -> &'static str {
+        "blibli.com"
+    }
+
+    /// Build Blibli search URL for a query
+    ///
+    /// Blibli has no condition filter, so `condition` is ignored.
+    fn build_search_url(&self, query: &str, filters: &SearchFilters, page: u32) -> String {
+        let mut params = Vec::new();
+        if let Some(min) = filters.min_price {
+            params.push(format!("minPrice={min}"));
+        }
+        if let Some(max) = filters.max_price {
+            params.push(format!("maxPrice={max}"));
+        }
+        // Blibli's "sort" codes
+        let sort = match filters.sort {
+            SortOrder::Relevance => None,
+            SortOrder::LowestPrice => Some(3),
+            SortOrder::HighestPrice => Some(4),
+            SortOrder::Newest => Some(5),
+            SortOrder::BestSelling => Some(7),
+        };
+        if let Some(sort) = sort {
+            params.push(format!("sort={sort}"));
+        }
+        if filters.official_store_only {
+            params.push("official=true".to_string());
+        }
+        if let Some(rating) = filters.rating_min.filter(|r| *r >= 1.0) {
+            params.push(format!("rating={}", rating.floor() as u8));
+        }
+        if page > 1 {
+            params.push(format!("page={page}"));
+        }
+
+        let mut url = format!("{}/cari/{}", BLIBLI_BASE_URL, urlencoding::encode(query));
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&params.join("&"));
+        }
+        url
+    }
+
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            marketplace: self.id(),
+            ready_selector: "a.elf-product-card",
+            product_count_script: PRODUCT_COUNT_SCRIPT,
+            timing: settings().render_timing(self.id()),
+        }
+    }
+
+    fn page_signatures(&self) -> &'static PageSignatures {
+        &PAGE_SIGNATURES
+    }
+
+    fn parse(&self, html: &str, limit: usize) -> Vec<Product> {
+        BlibliParser.parse_products_from_dom(html, limit)
+    }
+
+    fn product_render_options(&self) -> RenderOptions {
+        RenderOptions {
+            marketplace: self.id(),
+            ready_selector: r#".product-name, script[type="application/ld+json"]"#,
+            product_count_script: PRODUCT_PAGE_READY_SCRIPT,
+            timing: settings().render_timing(self.id()),
+        }
+    }
+
+    fn parse_product_detail(&self, html: &str, url: &str) -> Option<ProductDetail> {
+        BlibliParser.parse_product_page(html, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_search_url_with_filters() {
+        let filters = SearchFilters {
+            min_price: Some(50_000),
+            sort: SortOrder::BestSelling,
+            official_store_only: true,
+            ..SearchFilters::default()
+        };
+
+        assert_eq!(
+            BlibliMarketplace.build_search_url("sepatu lari", &SearchFilters::default(), 1),
+            "https://www.blibli.com/cari/sepatu%20lari"
+        );
+        assert_eq!(
+            BlibliMarketplace.build_search_url("sepatu lari", &filters, 3),
+            "https://www.blibli.com/cari/sepatu%20lari?minPrice=50000&sort=7&official=true&page=3"
+        );
+    }
+}
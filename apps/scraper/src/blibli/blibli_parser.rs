@@ -0,0 +1,356 @@
+use scraper::{Html, Selector};
+use tracing::{debug, info};
+
+use crate::config::{settings, BLIBLI_BASE_URL};
+use crate::marketplace::marketplace_model::{Product, ProductDetail, ProductVariant};
+use crate::metrics::metrics;
+use crate::popularity::{parse_rating, parse_review_count, parse_sold_count};
+use crate::pricing::{parse_discount_percent, parse_rupiah, PriceInfo};
+use crate::product_detail::{
+    all_texts, finish_product_detail, first_attr, first_multiline_text, first_text, image_sources,
+    parse_stock, parse_weight_grams, product_detail_from_json_ld,
+};
+
+/// Marketplace id stored in every parsed product
+const SOURCE: &str = "blibli";
+
+/// Turns Blibli HTML into products
+///
+/// Works on HTML alone, so it can be used on stored pages without a browser.
+pub struct BlibliParser;
+
+impl BlibliParser {
+    /// Parse a product page
+    ///
+    /// Blibli renders most product data client-side and publishes it as JSON-LD,
+    /// so the DOM is only used to fill what the JSON-LD leaves out. Pages without
+    /// a Blibli product name and price (block pages, interstitials) give `None`.
+    pub fn parse_product_page(&self, html: &str, url: &str) -> Option<ProductDetail> {
+        let document = Html::parse_document(html);
+        let mut detail = product_detail_from_json_ld(&document);
+
+        if detail.name.is_empty() {
+            detail.name = first_text(&document, ".product-name")?;
+        }
+        if detail.price_idr == 0 {
+            detail.price_idr = first_text(&document, ".product-price__after, .final-price")
+                .and_then(|t| parse_rupiah(&t))
+                .unwrap_or(0);
+        }
+        if detail.price_idr == 0 {
+            return None;
+        }
+        detail.original_price_idr = first_text(&document, ".product-price__before")
+            .and_then(|t| parse_rupiah(&t))
+            .or(detail.original_price_idr);
+        detail.discount_percent = first_text(&document, ".product-price__discount")
+            .and_then(|t| parse_discount_percent(&t))
+            .or(detail.discount_percent);
+
+        if detail.description.is_empty() {
+            detail.description = first_multiline_text(&document, ".product-description, #product-description")
+                .unwrap_or_default();
+        }
+        detail.images.extend(image_sources(&document, ".product-image img, .product-thumbnail img"));
+
+        if detail.variants.is_empty() {
+            detail.variants = all_texts(&document, ".product-variant__item, .attribute-options__item")
+                .into_iter()
+                .map(|name| ProductVariant { name, ..ProductVariant::default() })
+                .collect();
+        }
+        detail.stock = first_text(&document, ".product-stock")
+            .and_then(|t| parse_stock(&t))
+            .or(detail.stock);
+        if detail.weight_grams.is_none() {
+            detail.weight_grams = all_texts(&document, ".product-specification li, .product-specification tr")
+                .iter()
+                .filter(|row| row.to_lowercase().contains("berat"))
+                .find_map(|row| parse_weight_grams(row));
+        }
+
+        detail.shop_name = first_text(&document, ".seller-name, .merchant-name");
+        detail.shop_badge = first_attr(&document, ".seller-badge img, .merchant-badge img", "alt")
+            .or_else(|| first_text(&document, ".official-store-badge").map(|_| "Official Store".to_string()));
+        if detail.categories.is_empty() {
+            detail.categories = all_texts(&document, ".breadcrumb li");
+        }
+
+        if detail.rating.is_none() {
+            detail.rating = first_text(&document, ".product-rating__value").and_then(|t| parse_rating(&t));
+        }
+        if detail.review_count.is_none() {
+            detail.review_count = first_text(&document, ".product-rating__count").and_then(|t| parse_review_count(&t));
+        }
+        detail.sold_min = first_text(&document, ".product-sold").and_then(|t| parse_sold_count(&t));
+
+        detail.product_url = url.to_string();
+        detail.source = SOURCE.to_string();
+        finish_product_detail(&mut detail);
+        Some(detail)
+    }
+
+    /// Extract price from product card with proper handling of discounts
+    fn extract_price_from_card(&self, card: &scraper::ElementRef) -> Option<PriceInfo> {
+        // Try to get the discounted price first (els-product__fixed-price)
+        let fixed_price_selector = Selector::parse(".els-product__fixed-price").unwrap();
+        let fixed_price = card
+            .select(&fixed_price_selector)
+            .next()
+            .and_then(|elem| parse_rupiah(&elem.text().collect::<String>()));
+
+        if let Some(price_idr) = fixed_price {
+            // Strikethrough price and promo label are only present on discounted products
+            let original_price_selector = Selector::parse(".els-product__discount-price").unwrap();
+            let original_price_idr = card.select(&original_price_selector).next().and_then(|elem| {
+                elem.value()
+                    .attr("title")
+                    .and_then(parse_rupiah)
+                    .or_else(|| parse_rupiah(&elem.text().collect::<String>()))
+            });
+
+            let discount_label_selector = Selector::parse(".els-promo-label__text").unwrap();
+            let discount_percent = card
+                .select(&discount_label_selector)
+                .find_map(|elem| parse_discount_percent(&elem.text().collect::<String>()));
+
+            return Some(PriceInfo::new(price_idr, original_price_idr, discount_percent));
+        }
+
+        // Fallback: try to get any price text that starts with "Rp"
+        let all_texts: Vec<String> = card.text().map(|s| s.to_string()).collect();
+        for text in all_texts {
+            let trimmed = text.trim();
+            if let Some(remaining) = trimmed.strip_prefix("Rp") {
+                // Extract the price part by finding valid Rupiah format: Rp followed by digits and dots
+                // Valid format: RpXX.XXX or RpXXX.XXX.XXX etc., but stop if pattern breaks
+                let mut price_part = String::new();
+                let mut dot_count = 0;
+                let mut digits_after_last_dot = 0;
+
+                for ch in remaining.chars() {
+                    if ch.is_numeric() {
+                        price_part.push(ch);
+                        if dot_count > 0 {
+                            digits_after_last_dot += 1;
+                            // Stop after 3 digits following a dot (standard Rupiah format)
+                            if digits_after_last_dot >= 3 {
+                                break;
+                            }
+                        }
+                    } else if ch == '.' && dot_count < 1 {
+                        // Allow only one dot
+                        price_part.push(ch);
+                        dot_count += 1;
+                        digits_after_last_dot = 0;
+                    } else {
+                        // Stop at first invalid character
+                        break;
+                    }
+                }
+
+                if !price_part.is_empty() && price_part.len() >= 3 {
+                    if let Some(price_idr) = parse_rupiah(&price_part) {
+                        return Some(PriceInfo::new(price_idr, None, None));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Parse Blibli products from HTML using DOM selectors
+    pub fn parse_products_from_dom(&self, html: &str, limit: usize) -> Vec<Product> {
+        let document = Html::parse_document(html);
+        let card_selector = Selector::parse("a.elf-product-card").unwrap();
+        let img_selector = Selector::parse("img").unwrap();
+        let text_selector = Selector::parse("div").unwrap();
+        let mut products = Vec::new();
+        let mut seen_urls = std::collections::HashSet::new();
+        debug!("Searching for Blibli products...");
+        for card in document.select(&card_selector) {
+            if products.len() >= limit {
+                break;
+            }
+            let product_url = card.value().attr("href").map(|href| {
+                if href.starts_with("http") {
+                    href.to_string()
+                } else if href.starts_with('/') {
+                    format!("{BLIBLI_BASE_URL}{href}")
+                } else {
+                    format!("{BLIBLI_BASE_URL}/{href}")
+                }
+            }).unwrap_or_default();
+            if product_url.is_empty() || seen_urls.contains(&product_url) {
+                continue;
+            }
+            seen_urls.insert(product_url.clone());
+            let image_url = card.select(&img_selector).next().and_then(|img| img.value().attr("src").or_else(|| img.value().attr("data-src"))).map(|s| s.to_string()).unwrap_or_default();
+
+            // Extract price more precisely from price wrapper
+            let Some(price_info) = self.extract_price_from_card(&card) else {
+                continue;
+            };
+
+            let all_texts: Vec<String> = card.select(&text_selector).map(|div| div.text().collect::<String>().trim().to_string()).filter(|text| !text.is_empty()).collect();
+            let name = all_texts.iter().filter(|text| text.len() > 10 && !text.starts_with("Rp") && !text.contains("terjual")).max_by_key(|text| text.len()).cloned().unwrap_or_else(|| "Unknown Product".to_string());
+            let rating = all_texts.iter().filter(|text| text.contains('.') || text.contains(',')).find_map(|text| parse_rating(text));
+            let review_count = all_texts.iter().find_map(|text| parse_review_count(text));
+            let sold = all_texts.iter().find(|text| text.to_lowercase().contains("terjual") || text.to_lowercase().contains("rb terjual")).cloned();
+            let sold_min = sold.as_deref().and_then(parse_sold_count);
+            let shop_location = all_texts.iter().find(|text| settings().parsing.indonesian_cities.iter().any(|city| text.contains(city.as_str()))).cloned();
+            if !name.is_empty() {
+                products.push(Product {
+                    name,
+                    price: price_info.display(),
+                    price_idr: price_info.price_idr,
+                    original_price_idr: price_info.original_price_idr,
+                    discount_percent: price_info.discount_percent,
+                    rating,
+                    review_count,
+                    image_url,
+                    product_url,
+                    shop_location,
+                    sold,
+                    sold_min,
+                    source: SOURCE.to_string(),
+                });
+            }
+        }
+        info!("Extracted {} Blibli products from DOM", products.len());
+        metrics().parses.with_label_values(&[SOURCE, "dom"]).inc();
+        products
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_price_from_card_with_discount() {
+        let html = r#"
+            <a class="elf-product-card">
+                <div class="els-product__price-wrapper">
+                    <div class="els-product__price-top">
+                        <div class="els-product__fixed-price-wrapper">
+                            <div title="60.270" class="els-product__fixed-price">
+                                <span class="els-product__fixed-price-label">Rp</span>
+                                <span>60.270</span>
+                            </div>
+                        </div>
+                        <span title="86.100" class="els-product__discount-price">86.100</span>
+                        <div class="els-product__discount-wrapper">
+                            <div class="els-promo-label b-discount b-small">
+                                <div class="els-promo-label__text">30% </div>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </a>
+        "#;
+
+        let document = Html::parse_document(html);
+        let card_selector = Selector::parse("a.elf-product-card").unwrap();
+        let card = document.select(&card_selector).next().unwrap();
+
+        let price = BlibliParser.extract_price_from_card(&card).unwrap();
+
+        assert_eq!(price.display(), "Rp60.270");
+        assert_eq!(price.price_idr, 60_270);
+        assert_eq!(price.original_price_idr, Some(86_100));
+        assert_eq!(price.discount_percent, Some(30));
+    }
+
+    #[test]
+    fn test_extract_price_from_card_without_discount() {
+        let html = r#"
+            <a class="elf-product-card">
+                <div>Rp45.000</div>
+                <div>Product name</div>
+            </a>
+        "#;
+
+        let document = Html::parse_document(html);
+        let card_selector = Selector::parse("a.elf-product-card").unwrap();
+        let card = document.select(&card_selector).next().unwrap();
+
+        let price = BlibliParser.extract_price_from_card(&card).unwrap();
+
+        assert_eq!(price.display(), "Rp45.000");
+        assert_eq!(price.price_idr, 45_000);
+        assert_eq!(price.original_price_idr, None);
+        assert_eq!(price.discount_percent, None);
+    }
+
+    #[test]
+    fn test_extract_price_from_card_mixed_content() {
+        let html = r#"
+            <a class="elf-product-card">
+                <div>Rp58.05061.0505% Diskon 50%</div>
+                <div>Product name with discount</div>
+            </a>
+        "#;
+
+        let document = Html::parse_document(html);
+        let card_selector = Selector::parse("a.elf-product-card").unwrap();
+        let card = document.select(&card_selector).next().unwrap();
+
+        let price = BlibliParser.extract_price_from_card(&card).unwrap();
+
+        // Should extract clean price without discount info
+        assert_eq!(price.display(), "Rp58.050");
+        assert_eq!(price.price_idr, 58_050);
+    }
+
+    #[test]
+    fn test_parse_product_page_completes_json_ld_from_dom() {
+        let html = r#"
+            <script type="application/ld+json">
+                {"@type": "Product", "name": "Sepatu Lari Pria", "image": "https://static-src.com/sepatu.jpg",
+                 "offers": {"@type": "Offer", "price": 450000, "availability": "https://schema.org/InStock"}}
+            </script>
+            <ul class="breadcrumb"><li>Home</li><li>Olahraga</li><li>Sepatu Lari</li></ul>
+            <div class="product-price__before">Rp600.000</div>
+            <div class="product-description"><p>Ringan dan empuk.</p><p>Ukuran 39-44.</p></div>
+            <div class="seller-name">Sport Center</div>
+            <div class="seller-badge"><img alt="Official Store" src="https://static-src.com/os.png"></div>
+            <table class="product-specification"><tr><td>Berat</td><td>1,2 kg</td></tr></table>
+        "#;
+
+        let detail = BlibliParser
+            .parse_product_page(html, "https://www.blibli.com/p/sepatu-lari-pria/ps--SPC-123")
+            .unwrap();
+
+        assert_eq!(detail.name, "Sepatu Lari Pria");
+        assert_eq!(detail.price, "Rp450.000");
+        assert_eq!(detail.original_price_idr, Some(600_000));
+        assert_eq!(detail.discount_percent, Some(25));
+        assert_eq!(detail.description, "Ringan dan empuk.\nUkuran 39-44.");
+        assert_eq!(detail.images, vec!["https://static-src.com/sepatu.jpg"]);
+        assert_eq!(detail.weight_grams, Some(1_200));
+        assert_eq!(detail.shop_name.as_deref(), Some("Sport Center"));
+        assert_eq!(detail.shop_badge.as_deref(), Some("Official Store"));
+        assert_eq!(detail.categories, vec!["Olahraga", "Sepatu Lari"]);
+        assert_eq!(detail.source, "blibli");
+    }
+
+    #[test]
+    fn test_parse_product_page_rejects_block_page() {
+        let html = r#"
+            <html><head><title>Attention Required! | Cloudflare</title></head>
+            <body><h1>Sorry, you have been blocked</h1>
+            <h2>You are unable to access blibli.com</h2></body></html>
+        "#;
+
+        assert!(BlibliParser.parse_product_page(html, "https://www.blibli.com/p/x/ps--SPC-1").is_none());
+    }
+
+    #[test]
+    fn test_parse_product_page_requires_a_price() {
+        let html = r#"<div class="product-name">Sepatu Lari Pria</div><div class="product-price__after">Hubungi penjual</div>"#;
+
+        assert!(BlibliParser.parse_product_page(html, "https://www.blibli.com/p/x/ps--SPC-1").is_none());
+    }
+}
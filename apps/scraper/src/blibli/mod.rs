@@ -1,11 +1,5 @@
-pub mod blibli_controller;
-pub mod blibli_dto;
-pub mod blibli_model;
-pub mod blibli_repository;
-pub mod blibli_service;
+pub mod blibli_marketplace;
+pub mod blibli_parser;
 
-use axum::Router;
-
-pub fn router() -> Router {
-    blibli_controller::router()
-}
+pub use blibli_marketplace::BlibliMarketplace;
+pub use blibli_parser::BlibliParser;
@@ -0,0 +1,165 @@
+/// What a fetched marketplace page turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageKind {
+    /// Products were found on it
+    Results,
+    /// A regular page without products, e.g. a search nothing matches
+    Empty,
+    /// An "access denied" or bot interstitial page
+    Blocked,
+    /// A challenge asking to prove a person is browsing
+    Captcha,
+    /// The marketplace's own error page
+    ErrorPage,
+    /// A page without products that is not recognizably the marketplace's, e.g. an unknown interstitial
+    Unrecognized,
+}
+
+impl PageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Results => "results",
+            Self::Empty => "empty",
+            Self::Blocked => "blocked",
+            Self::Captcha => "captcha",
+            Self::ErrorPage => "error_page",
+            Self::Unrecognized => "unrecognized",
+        }
+    }
+
+    /// Classify a page from which `products` products were parsed
+    ///
+    /// Pages with products are always results. Otherwise the marketplace's
+    /// `signatures` and [`COMMON_SIGNATURES`] are looked up in the HTML. A page
+    /// matching none of them is only empty when it is recognizably a search page,
+    /// anything else is unrecognized rather than passing for "no products".
+    pub fn classify(html: &str, products: usize, signatures: &PageSignatures) -> Self {
+        if products > 0 {
+            return Self::Results;
+        }
+
+        let html = html.to_lowercase();
+        let matches = |fragments: fn(&PageSignatures) -> &'static [&'static str]| {
+            [signatures, &COMMON_SIGNATURES]
+                .into_iter()
+                .flat_map(fragments)
+                .any(|fragment| html.contains(fragment))
+        };
+        // A real "no results" page can load captcha scripts too, so it is recognized first
+        if matches(|s| s.empty) {
+            Self::Empty
+        } else if matches(|s| s.captcha) {
+            Self::Captcha
+        } else if matches(|s| s.blocked) {
+            Self::Blocked
+        } else if matches(|s| s.error) {
+            Self::ErrorPage
+        } else if matches(|s| s.search_page) {
+            Self::Empty
+        } else {
+            Self::Unrecognized
+        }
+    }
+
+    /// Classify a product page, `parsed` telling whether a product was read from it
+    /// and `priced` whether that product had a price
+    ///
+    /// A loose selector can read a product from a block page, so a product without
+    /// a price only counts when the page is no captcha or block page. Priced
+    /// products are trusted, as real product pages can load captcha scripts too.
+    pub fn classify_product(html: &str, parsed: bool, priced: bool, signatures: &PageSignatures) -> Self {
+        if priced {
+            return Self::Results;
+        }
+        match Self::classify(html, 0, signatures) {
+            kind @ (Self::Captcha | Self::Blocked) => kind,
+            _ if parsed => Self::Results,
+            kind => kind,
+        }
+    }
+}
+
+/// Lower case text fragments that identify a marketplace's special pages
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PageSignatures {
+    /// Its "no products found" page
+    pub empty: &'static [&'static str],
+    pub captcha: &'static [&'static str],
+    pub blocked: &'static [&'static str],
+    pub error: &'static [&'static str],
+    /// Found on every regular search results page, so one without products is taken as empty
+    pub search_page: &'static [&'static str],
+}
+
+impl PageSignatures {
+    pub const NONE: Self = Self { empty: &[], captcha: &[], blocked: &[], error: &[], search_page: &[] };
+}
+
+/// Pages of the bot protection and CDN vendors marketplaces commonly sit behind
+pub const COMMON_SIGNATURES: PageSignatures = PageSignatures {
+    empty: &[],
+    captcha: &[
+        "g-recaptcha",
+        "h-captcha",
+        "px-captcha",
+        "captcha-delivery.com",
+        "cf-turnstile",
+        "challenges.cloudflare.com",
+        "verify you are human",
+    ],
+    blocked: &[
+        "access denied",
+        "you don't have permission to access",
+        "request unsuccessful. incapsula",
+        "attention required! | cloudflare",
+        "pardon our interruption",
+        "akses ditolak",
+    ],
+    error: &["502 bad gateway", "503 service unavailable", "504 gateway time-out", "internal server error"],
+    search_page: &[],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNATURES: PageSignatures = PageSignatures {
+        empty: &["produk nggak ditemukan"],
+        error: &["terjadi kesalahan"],
+        search_page: &["data-testid=\"divsrpcontentproducts\""],
+        ..PageSignatures::NONE
+    };
+
+    #[test]
+    fn test_pages_are_classified_by_signature() {
+        let classify = |html: &str| PageKind::classify(html, 0, &SIGNATURES);
+
+        assert_eq!(PageKind::classify("<title>Access Denied</title>", 3, &SIGNATURES), PageKind::Results);
+        assert_eq!(classify("<html><title>Access Denied</title>Reference #18.2f</html>"), PageKind::Blocked);
+        assert_eq!(classify(r#"<script src="https://geo.captcha-delivery.com/captcha/"></script>"#), PageKind::Captcha);
+        assert_eq!(classify("<h1>Maaf, terjadi kesalahan</h1>"), PageKind::ErrorPage);
+        // Search pages load the login captcha script even when nothing matched
+        assert_eq!(classify(r#"<div class="g-recaptcha"></div><p>Oops, produk nggak ditemukan</p>"#), PageKind::Empty);
+        assert_eq!(classify(r#"<div data-testid="divSRPContentProducts"></div>"#), PageKind::Empty);
+    }
+
+    #[test]
+    fn test_unknown_page_without_products_is_unrecognized() {
+        let classify = |html: &str| PageKind::classify(html, 0, &SIGNATURES);
+
+        assert_eq!(classify("<html><body></body></html>"), PageKind::Unrecognized);
+        assert_eq!(classify("<html><title>Checking your browser</title><p>Please wait...</p></html>"), PageKind::Unrecognized);
+    }
+
+    #[test]
+    fn test_product_without_price_on_block_page_is_blocked() {
+        let classify = |html: &str, parsed, priced| PageKind::classify_product(html, parsed, priced, &SIGNATURES);
+        let block_page = "<title>Attention Required! | Cloudflare</title><h1>Sorry, you have been blocked</h1>";
+
+        assert_eq!(classify(block_page, true, false), PageKind::Blocked);
+        assert_eq!(classify(r#"<div class="g-recaptcha"></div>"#, true, false), PageKind::Captcha);
+        assert_eq!(classify(r#"<div class="g-recaptcha"></div>"#, true, true), PageKind::Results);
+        assert_eq!(classify("<h1>Sepatu Lari</h1>", true, false), PageKind::Results);
+        assert_eq!(classify("<h1>Sepatu Lari</h1>", false, false), PageKind::Unrecognized);
+    }
+}
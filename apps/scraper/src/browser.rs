@@ -1,79 +1,90 @@
 use anyhow::{Context, Result};
 use headless_chrome::{Browser, LaunchOptions};
+use std::ffi::OsString;
+use std::sync::Arc;
+use tracing::{debug, info};
 
 use crate::config::*;
+use crate::proxy_pool::Proxy;
+
+/// Chrome flags of every browser, after `--user-agent`
+const CHROME_ARGS: &[&str] = &[
+    "--disable-blink-features=AutomationControlled",
+    "--lang=id-ID",
+    "--accept-lang=id-ID",
+    "--disable-gpu",
+    "--no-sandbox",
+    "--disable-images",
+    "--disable-dev-shm-usage",
+    "--disable-software-rasterizer",
+    "--disable-background-timer-throttling",
+    "--disable-backgrounding-occluded-windows",
+    "--disable-renderer-backgrounding",
+    "--disable-features=VizDisplayCompositor",
+];
 
 pub struct BrowserClient {
     browser: Browser,
+    /// Proxy every tab of this browser goes through
+    proxy: Option<Arc<Proxy>>,
 }
 
 impl BrowserClient {
-    pub fn new() -> Result<Self> {
-        println!("🚀 Initializing browser client...");
-        let launch_options = Self::create_launch_options();
-        println!("⚙️  Browser launch options configured");
+    pub fn new(proxy: Option<Arc<Proxy>>) -> Result<Self> {
+        match &proxy {
+            Some(proxy) => info!("Initializing browser client through proxy {proxy}..."),
+            None => info!("Initializing browser client..."),
+        }
+        let args = Self::launch_args();
+        let launch_options = Self::create_launch_options(proxy.as_ref().map(|proxy| proxy.server()), &args)?;
+        debug!("Browser launch options configured");
         
         let browser = Browser::new(launch_options)
             .context("Failed to launch browser")?;
         
-        println!("✅ Browser launched successfully");
-        Ok(Self { browser })
+        info!("Browser launched successfully");
+        Ok(Self { browser, proxy })
     }
 
-    fn create_launch_options() -> LaunchOptions<'static> {
-        use std::ffi::OsStr;
-        
-        let user_agent_arg = Box::leak(format!("--user-agent={USER_AGENT}").into_boxed_str());
-        
-        // Set browser path from environment variable if available
-        if let Ok(chrome_bin) = std::env::var("CHROME_BIN") {
-            LaunchOptions::default_builder()
-                .headless(true)
-                .window_size(Some((BROWSER_WINDOW_WIDTH, BROWSER_WINDOW_HEIGHT)))
-                .path(Some(chrome_bin.into()))
-                .args(vec![
-                    OsStr::new(user_agent_arg),
-                    OsStr::new("--disable-blink-features=AutomationControlled"),
-                    OsStr::new("--lang=id-ID"),
-                    OsStr::new("--accept-lang=id-ID"),
-                    OsStr::new("--disable-gpu"),
-                    OsStr::new("--no-sandbox"),
-                    OsStr::new("--disable-images"),
-                    OsStr::new("--disable-dev-shm-usage"),
-                    OsStr::new("--disable-software-rasterizer"),
-                    OsStr::new("--disable-background-timer-throttling"),
-                    OsStr::new("--disable-backgrounding-occluded-windows"),
-                    OsStr::new("--disable-renderer-backgrounding"),
-                    OsStr::new("--disable-features=VizDisplayCompositor"),
-                ])
-                .build()
-                .expect("Failed to build launch options")
-        } else {
-            LaunchOptions::default_builder()
-                .headless(true)
-                .window_size(Some((BROWSER_WINDOW_WIDTH, BROWSER_WINDOW_HEIGHT)))
-                .args(vec![
-                    OsStr::new(user_agent_arg),
-                    OsStr::new("--disable-blink-features=AutomationControlled"),
-                    OsStr::new("--lang=id-ID"),
-                    OsStr::new("--accept-lang=id-ID"),
-                    OsStr::new("--disable-gpu"),
-                    OsStr::new("--no-sandbox"),
-                    OsStr::new("--disable-images"),
-                    OsStr::new("--disable-dev-shm-usage"),
-                    OsStr::new("--disable-software-rasterizer"),
-                    OsStr::new("--disable-background-timer-throttling"),
-                    OsStr::new("--disable-backgrounding-occluded-windows"),
-                    OsStr::new("--disable-renderer-backgrounding"),
-                    OsStr::new("--disable-features=VizDisplayCompositor"),
-                ])
-                .build()
-                .expect("Failed to build launch options")
-        }
+    /// Chrome flags of a new browser, owned so the launch options can borrow them
+    fn launch_args() -> Vec<OsString> {
+        let user_agent_arg = format!("--user-agent={}", settings().browser.user_agent);
+        std::iter::once(OsString::from(user_agent_arg))
+            .chain(CHROME_ARGS.iter().map(OsString::from))
+            .collect()
+    }
+
+    fn create_launch_options<'a>(proxy_server: Option<&'a str>, args: &'a [OsString]) -> Result<LaunchOptions<'a>> {
+        let browser_settings = &settings().browser;
+        LaunchOptions::default_builder()
+            .headless(true)
+            .window_size(Some((browser_settings.window_width, browser_settings.window_height)))
+            .idle_browser_timeout(get_browser_idle_timeout())
+            .path(Some(&browser_settings.chrome_bin).filter(|path| !path.is_empty()).map(Into::into))
+            .proxy_server(proxy_server)
+            .args(args.iter().map(OsString::as_os_str).collect())
+            .build()
+            .context("Failed to build launch options")
+    }
+
+    /// Check that the browser process still answers DevTools commands
+    pub fn is_healthy(&self) -> bool {
+        self.browser.get_version().is_ok()
     }
 
-    pub fn new_tab(&self) -> Result<std::sync::Arc<headless_chrome::Tab>> {
-        self.browser.new_tab()
-            .context("Failed to create new tab")
+    pub fn proxy(&self) -> Option<&Arc<Proxy>> {
+        self.proxy.as_ref()
+    }
+
+    /// Open a tab, answering the proxy's login challenge when it has credentials
+    pub fn new_tab(&self) -> Result<Arc<headless_chrome::Tab>> {
+        let tab = self.browser.new_tab()
+            .context("Failed to create new tab")?;
+        if let Some((username, password)) = self.proxy.as_ref().and_then(|proxy| proxy.credentials()) {
+            tab.enable_fetch(None, Some(true))
+                .context("Failed to intercept proxy login")?;
+            tab.authenticate(Some(username.clone()), Some(password.clone()))?;
+        }
+        Ok(tab)
     }
 }
@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use headless_chrome::Tab;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::browser::BrowserClient;
+use crate::error::ScraperError;
+use crate::metrics::metrics;
+use crate::proxy_pool::{Proxy, ProxyPool};
+
+/// One browser process in the pool together with the tabs it keeps for reuse
+#[derive(Default)]
+struct BrowserSlot {
+    client: Option<Arc<BrowserClient>>,
+    idle_tabs: Vec<Arc<Tab>>,
+}
+
+/// Pool of long-lived browser instances shared by every request
+///
+/// Browsers are launched lazily on first use, health-checked each time a tab is
+/// handed out and relaunched when the process no longer responds. The number of
+/// tabs open at the same time across the whole pool is capped by a semaphore.
+/// Each browser goes through the next proxy of the [`ProxyPool`] when it is
+/// launched, and is relaunched through another once its proxy is quarantined.
+pub struct BrowserPool {
+    slots: Vec<Mutex<BrowserSlot>>,
+    tab_permits: Arc<Semaphore>,
+    next_slot: AtomicUsize,
+    max_idle_tabs_per_browser: usize,
+    proxies: Arc<ProxyPool>,
+}
+
+impl BrowserPool {
+    pub fn new(size: usize, max_open_tabs: usize, proxies: Arc<ProxyPool>) -> Self {
+        let size = size.max(1);
+        let max_open_tabs = max_open_tabs.max(1);
+
+        Self {
+            slots: (0..size).map(|_| Mutex::new(BrowserSlot::default())).collect(),
+            tab_permits: Arc::new(Semaphore::new(max_open_tabs)),
+            next_slot: AtomicUsize::new(0),
+            max_idle_tabs_per_browser: max_open_tabs.div_ceil(size),
+            proxies,
+        }
+    }
+
+    /// Number of browser instances in the pool
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Number of tabs that can still be handed out before callers have to wait
+    pub fn available_tabs(&self) -> usize {
+        self.tab_permits.available_permits()
+    }
+
+    /// Borrow a tab, waiting while the pool is at its open-tab limit
+    ///
+    /// The tab goes back to its browser when the returned guard is dropped.
+    pub async fn acquire_tab(self: &Arc<Self>) -> Result<PooledTab> {
+        let permit = Arc::clone(&self.tab_permits)
+            .acquire_owned()
+            .await
+            .context("Browser pool is closed")?;
+
+        // Launching a browser and opening a tab block, keep them off the async workers
+        let slot_index = self.next_slot.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let pool = Arc::clone(self);
+        let (tab, proxy) = tokio::task::spawn_blocking(move || pool.checkout_tab(slot_index))
+            .await
+            .context("Browser task panicked")??;
+
+        metrics().active_tabs.inc();
+        Ok(PooledTab {
+            tab: Some(tab),
+            pool: Arc::clone(self),
+            slot_index,
+            proxy,
+            _permit: permit,
+        })
+    }
+
+    fn lock_slot(&self, slot_index: usize) -> MutexGuard<'_, BrowserSlot> {
+        self.slots[slot_index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A tab of the slot's browser and the proxy that browser goes through
+    fn checkout_tab(&self, slot_index: usize) -> Result<(Arc<Tab>, Option<Arc<Proxy>>)> {
+        let mut slot = self.lock_slot(slot_index);
+        let client = self.ensure_browser(&mut slot, slot_index)?;
+        let proxy = client.proxy().cloned();
+
+        while let Some(tab) = slot.idle_tabs.pop() {
+            if tab.get_target_info().is_ok() {
+                debug!("Reusing tab from browser #{slot_index}");
+                return Ok((tab, proxy));
+            }
+        }
+
+        info!("Opening new tab in browser #{slot_index}");
+        Ok((client.new_tab()?, proxy))
+    }
+
+    /// Return the slot's browser, launching or relaunching it when needed
+    fn ensure_browser(&self, slot: &mut BrowserSlot, slot_index: usize) -> Result<Arc<BrowserClient>> {
+        if let Some(client) = &slot.client {
+            match self.proxy_to_replace(client.proxy()) {
+                None if client.is_healthy() => return Ok(Arc::clone(client)),
+                None => warn!("Browser #{slot_index} is not responding, relaunching..."),
+                Some(proxy) => info!("Proxy {proxy} of browser #{slot_index} is quarantined, relaunching through another"),
+            }
+            slot.client = None;
+            slot.idle_tabs.clear();
+        }
+
+        let client = BrowserClient::new(self.proxies.next()).map_err(|e| {
+            metrics().browser_launch_failures.inc();
+            ScraperError::BrowserLaunch(format!("{e:#}"))
+        })?;
+        let client = Arc::new(client);
+        slot.client = Some(Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// The browser's proxy when it is quarantined and another proxy is usable
+    ///
+    /// With every proxy quarantined a relaunch would get a quarantined one
+    /// again, so the browser is kept until one is released.
+    fn proxy_to_replace<'a>(&self, proxy: Option<&'a Arc<Proxy>>) -> Option<&'a Arc<Proxy>> {
+        proxy.filter(|proxy| proxy.is_quarantined() && self.proxies.has_available())
+    }
+
+    fn release_tab(&self, slot_index: usize, tab: Arc<Tab>) {
+        // Stop the previous page from running scripts while the tab sits idle
+        if let Err(e) = tab.navigate_to("about:blank") {
+            // A dropped tab stays open in Chrome, close it so it does not outlive its permit
+            warn!("Failed to blank tab of browser #{slot_index}, closing it: {e}");
+            let _ = tab.close(false);
+            return;
+        }
+
+        let mut slot = self.lock_slot(slot_index);
+        if slot.idle_tabs.len() < self.max_idle_tabs_per_browser {
+            slot.idle_tabs.push(tab);
+        } else {
+            let _ = tab.close(false);
+        }
+    }
+}
+
+/// Tab borrowed from a [`BrowserPool`]
+///
+/// Dropping it navigates the tab to a blank page, which blocks: drop it on a
+/// blocking thread.
+pub struct PooledTab {
+    tab: Option<Arc<Tab>>,
+    pool: Arc<BrowserPool>,
+    slot_index: usize,
+    proxy: Option<Arc<Proxy>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledTab {
+    /// Proxy the tab's browser goes through
+    pub fn proxy(&self) -> Option<Arc<Proxy>> {
+        self.proxy.clone()
+    }
+}
+
+impl Deref for PooledTab {
+    type Target = Tab;
+
+    fn deref(&self) -> &Tab {
+        self.tab.as_ref().expect("pooled tab used after release")
+    }
+}
+
+impl Drop for PooledTab {
+    fn drop(&mut self) {
+        if let Some(tab) = self.tab.take() {
+            self.pool.release_tab(self.slot_index, tab);
+        }
+        metrics().active_tabs.dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProxySettings;
+
+    #[test]
+    fn test_browser_is_kept_while_every_proxy_is_quarantined() {
+        let settings = ProxySettings {
+            urls: vec!["http://10.0.0.1:3128".to_string(), "http://10.0.0.2:3128".to_string()],
+            quarantine_after: 1,
+            quarantine_secs: 60,
+        };
+        let pool = BrowserPool::new(1, 1, Arc::new(ProxyPool::new(&settings).unwrap()));
+        let [first, second] = pool.proxies.proxies() else { unreachable!() };
+
+        first.record_failure("blocked");
+        assert!(pool.proxy_to_replace(Some(first)).is_some());
+        assert!(pool.proxy_to_replace(Some(second)).is_none());
+
+        second.record_failure("blocked");
+        assert!(pool.proxy_to_replace(Some(first)).is_none());
+        assert!(pool.proxy_to_replace(Some(second)).is_none());
+        assert!(pool.proxy_to_replace(None).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{bail, Result};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::info;
+
+use crate::cache::cache_dto::{
+    CacheInvalidateQuery, CacheInvalidateResult, CacheKeysQuery, CacheKeysResult, CacheStatsResult,
+};
+use crate::cache::{self, CacheGroupStats};
+use crate::error::{ApiResult, ScraperError};
+use crate::marketplace::marketplace_controller::ApiResponseError;
+use crate::marketplace::marketplace_dto::ApiResponse;
+use crate::marketplace::marketplace_registry::MarketplaceRegistry;
+use crate::state::AppState;
+
+/// Keys listed when a request has no `limit`
+const DEFAULT_KEYS_LIMIT: usize = 100;
+/// Upper bound for `limit` when listing keys
+const MAX_KEYS_LIMIT: usize = 1000;
+/// Keys read to report stored sizes, so a stats call stays cheap on a large cache
+const MAX_STATS_KEYS: usize = 10_000;
+
+/// HTTP handler listing cached entries
+#[utoipa::path(
+    get,
+    path = "/api/scraper/cache/keys",
+    params(CacheKeysQuery),
+    responses(
+        (status = 200, description = "Cached entries sorted by key", body = ApiResponse<CacheKeysResult>),
+        (status = 401, description = "cache.admin_token is unset or the request lacks it", body = inline(ApiResponseError)),
+        (status = 503, description = "Cache backend unavailable", body = inline(ApiResponseError))
+    ),
+    tag = "cache"
+)]
+pub async fn keys_handler(
+    State(state): State<AppState>,
+    Query(params): Query<CacheKeysQuery>,
+) -> ApiResult<CacheKeysResult> {
+    let limit = params.limit.unwrap_or(DEFAULT_KEYS_LIMIT).clamp(1, MAX_KEYS_LIMIT);
+
+    // One extra key tells whether the list was cut off
+    let mut keys = state
+        .cache
+        .keys(&params.prefix, limit + 1)
+        .await
+        .map_err(|e| ScraperError::CacheUnavailable(format!("Failed to list cache keys: {e}")))?;
+    let truncated = keys.len() > limit;
+    keys.truncate(limit);
+    let count = keys.len();
+    Ok((StatusCode::OK, Json(ApiResponse::success(CacheKeysResult { keys, truncated }, count))))
+}
+
+/// HTTP handler removing cached entries by marketplace, query or key prefix
+#[utoipa::path(
+    delete,
+    path = "/api/scraper/cache",
+    params(CacheInvalidateQuery),
+    responses(
+        (status = 200, description = "Matching entries were removed", body = ApiResponse<CacheInvalidateResult>),
+        (status = 400, description = "Nothing to invalidate given, or unknown marketplace", body = inline(ApiResponseError)),
+        (status = 401, description = "cache.admin_token is unset or the request lacks it", body = inline(ApiResponseError)),
+        (status = 503, description = "Cache backend unavailable", body = inline(ApiResponseError))
+    ),
+    tag = "cache"
+)]
+pub async fn invalidate_handler(
+    State(state): State<AppState>,
+    Query(params): Query<CacheInvalidateQuery>,
+) -> ApiResult<CacheInvalidateResult> {
+    let prefixes = invalidation_prefixes(&params, &state.marketplaces)
+        .map_err(|e| ScraperError::InvalidRequest(e.to_string()))?;
+
+    let mut deleted = 0;
+    for prefix in &prefixes {
+        deleted += state
+            .cache
+            .delete_prefix(prefix)
+            .await
+            .map_err(|e| ScraperError::CacheUnavailable(format!("Failed to invalidate '{prefix}': {e}")))?;
+    }
+    info!("Invalidated {deleted} cache entries under {prefixes:?}");
+
+    Ok((StatusCode::OK, Json(ApiResponse::success(CacheInvalidateResult { prefixes, deleted }, deleted))))
+}
+
+/// Key prefixes an invalidation request covers
+fn invalidation_prefixes(params: &CacheInvalidateQuery, marketplaces: &MarketplaceRegistry) -> Result<Vec<String>> {
+    if let Some(prefix) = &params.prefix {
+        return Ok(vec![prefix.clone()]);
+    }
+    if params.marketplace.is_none() && params.query.is_none() {
+        bail!("Give a marketplace, query or prefix to invalidate");
+    }
+
+    let prefixes = marketplaces
+        .resolve(params.marketplace.as_deref())?
+        .iter()
+        .flat_map(|marketplace| match &params.query {
+            Some(query) => vec![cache::search_prefix(marketplace.id(), Some(query))],
+            None => vec![cache::search_prefix(marketplace.id(), None), cache::product_prefix(marketplace.id())],
+        })
+        .collect();
+    Ok(prefixes)
+}
+
+/// HTTP handler reporting cache hits, misses and stored sizes
+#[utoipa::path(
+    get,
+    path = "/api/scraper/cache/stats",
+    responses(
+        (status = 200, description = "Counters since startup and current size, per kind and marketplace", body = ApiResponse<CacheStatsResult>),
+        (status = 401, description = "cache.admin_token is unset or the request lacks it", body = inline(ApiResponseError)),
+        (status = 503, description = "Cache backend unavailable", body = inline(ApiResponseError))
+    ),
+    tag = "cache"
+)]
+pub async fn stats_handler(State(state): State<AppState>) -> ApiResult<CacheStatsResult> {
+    let mut groups = state.cache_stats.snapshot();
+
+    // One extra key tells whether the sizes are partial
+    let mut stored = state
+        .cache
+        .keys("", MAX_STATS_KEYS + 1)
+        .await
+        .map_err(|e| ScraperError::CacheUnavailable(format!("Failed to read cache size: {e}")))?;
+    let sizes_truncated = stored.len() > MAX_STATS_KEYS;
+    stored.truncate(MAX_STATS_KEYS);
+    for entry in &stored {
+        let group = groups.entry(cache::key_group(&entry.key)).or_default();
+        group.entries += 1;
+        group.size_bytes += entry.size_bytes;
+    }
+
+    let mut total = CacheGroupStats::default();
+    for group in groups.values() {
+        total.add(group);
+    }
+
+    let count = groups.len();
+    let result = CacheStatsResult {
+        backend: state.cache.name().to_string(),
+        groups,
+        total,
+        sizes_truncated,
+        refreshing: state.refreshes.running(),
+    };
+    Ok((StatusCode::OK, Json(ApiResponse::success(result, count))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blibli::BlibliMarketplace;
+    use crate::tokopedia::TokopediaMarketplace;
+    use std::sync::Arc;
+
+    fn registry() -> MarketplaceRegistry {
+        let mut registry = MarketplaceRegistry::default();
+        registry.register(Arc::new(TokopediaMarketplace));
+        registry.register(Arc::new(BlibliMarketplace));
+        registry
+    }
+
+    fn params(marketplace: Option<&str>, query: Option<&str>, prefix: Option<&str>) -> CacheInvalidateQuery {
+        CacheInvalidateQuery {
+            marketplace: marketplace.map(String::from),
+            query: query.map(String::from),
+            prefix: prefix.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_invalidation_prefixes() {
+        let registry = registry();
+        assert_eq!(
+            invalidation_prefixes(&params(Some("tokopedia"), None, None), &registry).unwrap(),
+            vec!["search:tokopedia:", "product:tokopedia:"]
+        );
+        assert_eq!(
+            invalidation_prefixes(&params(None, Some(" iPhone"), None), &registry).unwrap(),
+            vec!["search:blibli:iphone", "search:tokopedia:iphone"]
+        );
+        assert_eq!(
+            invalidation_prefixes(&params(Some("blibli"), None, Some("product:")), &registry).unwrap(),
+            vec!["product:"]
+        );
+        assert!(invalidation_prefixes(&params(None, None, None), &registry).is_err());
+        assert!(invalidation_prefixes(&params(Some("shopee"), None, None), &registry).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::cache::{CacheGroupStats, CacheKeyInfo};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct CacheKeysQuery {
+    /// Only list keys starting with this prefix, e.g. "search:tokopedia:iphone" (default: all)
+    #[serde(default)]
+    pub prefix: String,
+    /// Maximum number of keys to return (default: 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct CacheInvalidateQuery {
+    /// Marketplace whose searches and products are removed (default: all with `query`)
+    pub marketplace: Option<String>,
+    /// Only remove searches whose normalized query starts with this text
+    pub query: Option<String>,
+    /// Raw key prefix to remove instead, e.g. "product:blibli:"; empty removes everything
+    pub prefix: Option<String>,
+}
+
+/// Stored entries matching a prefix
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheKeysResult {
+    pub keys: Vec<CacheKeyInfo>,
+    /// Whether more keys match than were returned
+    pub truncated: bool,
+}
+
+/// Outcome of an invalidation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheInvalidateResult {
+    /// Key prefixes that were removed
+    pub prefixes: Vec<String>,
+    /// Number of entries removed
+    pub deleted: usize,
+}
+
+/// Cache usage since startup
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CacheStatsResult {
+    /// Cache backend in use: redis or memory
+    pub backend: String,
+    /// Stats per kind and marketplace, e.g. "search:tokopedia"
+    pub groups: BTreeMap<String, CacheGroupStats>,
+    /// Sum of every group
+    pub total: CacheGroupStats,
+    /// Whether `entries` and `size_bytes` only count the first 10000 keys
+    pub sizes_truncated: bool,
+    /// Background refreshes running right now
+    pub refreshing: usize,
+}
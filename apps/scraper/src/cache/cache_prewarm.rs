@@ -0,0 +1,92 @@
+use anyhow::Result;
+use serde::de::IgnoredAny;
+use std::time::Duration;
+use tracing::{info, info_span, warn, Instrument};
+
+use crate::cache::{self, CachedEntry};
+use crate::config::settings;
+use crate::marketplace::marketplace_dto::SearchRequest;
+use crate::marketplace::marketplace_model::SearchFilters;
+use crate::marketplace::marketplace_service::MarketplaceService;
+use crate::state::AppState;
+
+/// Keep the configured popular searches cached
+///
+/// Every `prewarm.interval_secs`, starting right away, each query is scraped
+/// again on each marketplace whose cached results are missing or would turn
+/// stale before the next check. Searches with the default filters, first page
+/// and a `limit` up to `prewarm.limit` are then answered from the cache.
+/// Does nothing without `prewarm.queries`.
+pub fn spawn(state: AppState) -> Result<()> {
+    let prewarm = &settings().prewarm;
+    if prewarm.queries.is_empty() {
+        return Ok(());
+    }
+    let marketplaces = state.marketplaces.resolve(Some(&prewarm.marketplaces.join(",")))?;
+    let interval = Duration::from_secs(prewarm.interval_secs);
+
+    info!(
+        "Pre-warming {} quer{} on {} marketplace(s) every {interval:?}",
+        prewarm.queries.len(),
+        if prewarm.queries.len() == 1 { "y" } else { "ies" },
+        marketplaces.len()
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // A slow pass must not be followed by a burst of catch-up passes
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            for marketplace in &marketplaces {
+                let service = MarketplaceService::new(marketplace.clone(), &state);
+                for query in &prewarm.queries {
+                    let request =
+                        SearchRequest::new(query.clone(), prewarm.limit, None, None, SearchFilters::default());
+                    let key = cache::search_key(marketplace.id(), query, &request.filters, request.page);
+
+                    let age = match state.cache.get(&key).await {
+                        Ok(cached) => cached
+                            .and_then(|json| serde_json::from_str::<CachedEntry<IgnoredAny>>(&json).ok())
+                            .map(|entry| entry.age()),
+                        Err(e) => {
+                            warn!("Pre-warm skipped {key}: {} cache read failed: {e}", state.cache.name());
+                            continue;
+                        }
+                    };
+                    if !needs_warming(age, settings().cache.search_soft_ttl(), interval) {
+                        continue;
+                    }
+
+                    info!("Pre-warming '{query}' on {}", marketplace.display_name());
+                    let span = info_span!("prewarm", marketplace = marketplace.id(), query = %query);
+                    if let Err(e) = service.refresh_search(&request).instrument(span).await {
+                        warn!("Pre-warming '{query}' on {} failed: {e}", marketplace.display_name());
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Whether an entry of this age (`None` when missing) turns stale within `lead`
+fn needs_warming(age: Option<Duration>, soft_ttl: Duration, lead: Duration) -> bool {
+    age.is_none_or(|age| age + lead >= soft_ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_are_warmed_before_they_turn_stale() {
+        let soft_ttl = Duration::from_secs(3600);
+        let lead = Duration::from_secs(300);
+        assert!(needs_warming(None, soft_ttl, lead));
+        assert!(!needs_warming(Some(Duration::from_secs(60)), soft_ttl, lead));
+        assert!(needs_warming(Some(Duration::from_secs(3400)), soft_ttl, lead));
+        assert!(needs_warming(Some(Duration::from_secs(7200)), soft_ttl, lead));
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Cache keys being scraped again in the background
+///
+/// Many requests can find the same stale entry at once; only the first one
+/// starts a refresh, the others keep serving the stale result.
+#[derive(Default)]
+pub struct RefreshTracker {
+    keys: Mutex<HashSet<String>>,
+}
+
+impl RefreshTracker {
+    /// Claim `key` for a refresh, `None` while another refresh of it is running
+    ///
+    /// The claim is released when the returned guard is dropped.
+    pub fn start(self: &Arc<Self>, key: &str) -> Option<RefreshGuard> {
+        let mut keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        if !keys.insert(key.to_string()) {
+            return None;
+        }
+        Some(RefreshGuard {
+            tracker: Arc::clone(self),
+            key: key.to_string(),
+        })
+    }
+
+    /// Number of refreshes currently running
+    pub fn running(&self) -> usize {
+        self.keys.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+}
+
+/// A running refresh, see [`RefreshTracker::start`]
+pub struct RefreshGuard {
+    tracker: Arc<RefreshTracker>,
+    key: String,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        let mut keys = self.tracker.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        keys.remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_refresh_per_key_at_a_time() {
+        let tracker = Arc::new(RefreshTracker::default());
+        let guard = tracker.start("search:tokopedia:iphone").unwrap();
+        assert!(tracker.start("search:tokopedia:iphone").is_none());
+        assert!(tracker.start("search:blibli:iphone").is_some());
+        assert_eq!(tracker.running(), 1);
+
+        drop(guard);
+        assert_eq!(tracker.running(), 0);
+        assert!(tracker.start("search:tokopedia:iphone").is_some());
+    }
+}
@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use utoipa::ToSchema;
+
+use crate::cache::key_group;
+use crate::metrics::metrics;
+
+/// What happened when a request looked at the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheEvent {
+    /// A cached result answered the request
+    Hit,
+    /// A cached result past its soft TTL answered the request while it is refreshed
+    Stale,
+    /// Nothing usable was cached
+    Miss,
+    /// A cached result was older than the request's `max_age`
+    TooOld,
+    /// The request asked for `fresh` results
+    Bypassed,
+    /// A new result was stored
+    Write,
+    /// A cached result was scraped again in the background, because it was stale or pre-warmed
+    Refresh,
+}
+
+impl CacheEvent {
+    /// Label of the event in metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheEvent::Hit => "hit",
+            CacheEvent::Stale => "stale",
+            CacheEvent::Miss => "miss",
+            CacheEvent::TooOld => "too_old",
+            CacheEvent::Bypassed => "bypassed",
+            CacheEvent::Write => "write",
+            CacheEvent::Refresh => "refresh",
+        }
+    }
+}
+
+/// Counters and stored size of one group of cache keys, e.g. `search:tokopedia`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct CacheGroupStats {
+    pub hits: u64,
+    /// Cached results served past their soft TTL while being refreshed
+    pub stale: u64,
+    pub misses: u64,
+    /// Cached results rejected because of `max_age`
+    pub too_old: u64,
+    /// Lookups skipped because of `fresh=true`
+    pub bypassed: u64,
+    pub writes: u64,
+    /// Background refreshes of stale or pre-warmed results
+    pub refreshes: u64,
+    /// Entries currently stored
+    pub entries: u64,
+    /// Bytes currently stored
+    pub size_bytes: u64,
+}
+
+impl CacheGroupStats {
+    pub fn add(&mut self, other: &CacheGroupStats) {
+        self.hits += other.hits;
+        self.stale += other.stale;
+        self.misses += other.misses;
+        self.too_old += other.too_old;
+        self.bypassed += other.bypassed;
+        self.writes += other.writes;
+        self.refreshes += other.refreshes;
+        self.entries += other.entries;
+        self.size_bytes += other.size_bytes;
+    }
+
+    /// Share of lookups answered from the cache, stale answers included; `None`
+    /// before the first lookup. `fresh` bypasses are not lookups.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let answered = self.hits + self.stale;
+        let lookups = answered + self.misses + self.too_old;
+        (lookups > 0).then(|| answered as f64 / lookups as f64)
+    }
+}
+
+/// Cache lookups and writes since startup, grouped by kind and marketplace
+#[derive(Default)]
+pub struct CacheStats {
+    groups: Mutex<BTreeMap<String, CacheGroupStats>>,
+}
+
+impl CacheStats {
+    pub fn record(&self, key: &str, event: CacheEvent) {
+        let group_name = key_group(key);
+        let (kind, marketplace) = group_name.split_once(':').unwrap_or((&group_name, ""));
+        metrics()
+            .cache_events
+            .with_label_values(&[kind, marketplace, event.as_str()])
+            .inc();
+
+        let mut groups = self.groups.lock().unwrap_or_else(PoisonError::into_inner);
+        let group = groups.entry(group_name).or_default();
+        match event {
+            CacheEvent::Hit => group.hits += 1,
+            CacheEvent::Stale => group.stale += 1,
+            CacheEvent::Miss => group.misses += 1,
+            CacheEvent::TooOld => group.too_old += 1,
+            CacheEvent::Bypassed => group.bypassed += 1,
+            CacheEvent::Write => group.writes += 1,
+            CacheEvent::Refresh => group.refreshes += 1,
+        }
+    }
+
+    /// Counters of every group seen so far
+    pub fn snapshot(&self) -> BTreeMap<String, CacheGroupStats> {
+        self.groups.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_counted_per_group() {
+        let stats = CacheStats::default();
+        stats.record("search:tokopedia:iphone", CacheEvent::Miss);
+        stats.record("search:tokopedia:iphone", CacheEvent::Write);
+        stats.record("search:tokopedia:laptop:sort=newest", CacheEvent::Hit);
+        stats.record("product:blibli:https://www.blibli.com/p/1", CacheEvent::TooOld);
+
+        let snapshot = stats.snapshot();
+        let tokopedia = &snapshot["search:tokopedia"];
+        assert_eq!((tokopedia.hits, tokopedia.misses, tokopedia.writes), (1, 1, 1));
+        assert_eq!(snapshot["product:blibli"].too_old, 1);
+        assert_eq!(tokopedia.hit_ratio(), Some(0.5));
+        assert_eq!(snapshot["product:blibli"].hit_ratio(), Some(0.0));
+        assert_eq!(CacheGroupStats::default().hit_ratio(), None);
+    }
+}
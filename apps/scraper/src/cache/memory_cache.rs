@@ -0,0 +1,160 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::cache::{Cache, CacheKeyInfo};
+
+/// In-process LRU cache, used when Redis is not available
+///
+/// Holds at most `max_entries` values; the least recently used one is dropped
+/// to make room. Entries are not shared between instances of the scraper.
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, CacheEntry>>,
+}
+
+struct CacheEntry {
+    value: String,
+    /// `None` when the TTL reaches past what `Instant` can represent
+    expires_at: Option<Instant>,
+}
+
+impl CacheEntry {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        match entries.get(key) {
+            Some(entry) if entry.is_live(Instant::now()) => Ok(Some(entry.value.clone())),
+            Some(_) => {
+                entries.pop(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        let entry = CacheEntry {
+            value: value.to_string(),
+            expires_at: Instant::now().checked_add(ttl),
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .put(key.to_string(), entry);
+        Ok(())
+    }
+
+    async fn keys(&self, prefix: &str, limit: usize) -> Result<Vec<CacheKeyInfo>> {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut keys: Vec<CacheKeyInfo> = entries
+            .iter()
+            .filter(|(key, entry)| key.starts_with(prefix) && entry.is_live(now))
+            .map(|(key, entry)| CacheKeyInfo {
+                key: key.clone(),
+                size_bytes: entry.value.len() as u64,
+                ttl_secs: entry.expires_at.map(|expires_at| (expires_at - now).as_secs()),
+            })
+            .collect();
+        keys.sort_by(|a, b| a.key.cmp(&b.key));
+        keys.truncate(limit);
+        Ok(keys)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let keys: Vec<String> = entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        for key in &keys {
+            entries.pop(key);
+        }
+        Ok(keys.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn test_get_returns_stored_value() {
+        let cache = MemoryCache::new(10);
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        cache.set("a", "1", TTL).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap().as_deref(), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_missing() {
+        let cache = MemoryCache::new(10);
+        cache.set("a", "1", Duration::ZERO).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_huge_ttl_never_expires() {
+        let cache = MemoryCache::new(10);
+        cache.set("a", "1", Duration::MAX).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap().as_deref(), Some("1"));
+        assert_eq!(cache.keys("a", 1).await.unwrap()[0].ttl_secs, None);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_entry_is_evicted() {
+        let cache = MemoryCache::new(2);
+        cache.set("a", "1", TTL).await.unwrap();
+        cache.set("b", "2", TTL).await.unwrap();
+        cache.get("a").await.unwrap();
+        cache.set("c", "3", TTL).await.unwrap();
+
+        assert!(cache.get("a").await.unwrap().is_some());
+        assert_eq!(cache.get("b").await.unwrap(), None);
+        assert!(cache.get("c").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_keys_and_delete_by_prefix() {
+        let cache = MemoryCache::new(10);
+        cache.set("search:tokopedia:iphone", "[1]", TTL).await.unwrap();
+        cache.set("search:tokopedia:laptop", "[22]", TTL).await.unwrap();
+        cache.set("search:blibli:iphone", "[333]", TTL).await.unwrap();
+
+        let keys = cache.keys("search:tokopedia:", 10).await.unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].key, "search:tokopedia:iphone");
+        assert_eq!(keys[0].size_bytes, 3);
+        assert_eq!(cache.keys("", 1).await.unwrap().len(), 1);
+
+        assert_eq!(cache.delete_prefix("search:tokopedia:").await.unwrap(), 2);
+        assert_eq!(cache.get("search:tokopedia:iphone").await.unwrap(), None);
+        assert!(cache.get("search:blibli:iphone").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,256 @@
+pub mod cache_controller;
+pub mod cache_dto;
+pub mod cache_prewarm;
+pub mod cache_refresh;
+pub mod cache_stats;
+pub mod memory_cache;
+pub mod redis_cache;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use axum::{
+    extract::Request,
+    http::{header::AUTHORIZATION, HeaderMap},
+    middleware::{self, Next},
+    response::Response,
+    routing::{delete, get},
+    Router,
+};
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use subtle::ConstantTimeEq;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::config::{settings, CacheBackend, CacheSettings};
+use crate::error::ScraperError;
+use crate::marketplace::marketplace_model::SearchFilters;
+use crate::state::AppState;
+
+pub use cache_refresh::RefreshTracker;
+pub use cache_stats::{CacheEvent, CacheGroupStats, CacheStats};
+pub use memory_cache::MemoryCache;
+pub use redis_cache::RedisCache;
+
+/// Key-value store for scraped results, shared by every request
+///
+/// Values are serialized JSON. Backends decide how entries expire and are evicted,
+/// callers only give each entry a time to live.
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// Short name used in logs, e.g. `redis`
+    fn name(&self) -> &'static str;
+
+    /// Value stored under `key`, `None` when missing or expired
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Store `value` under `key` for `ttl`
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()>;
+
+    /// Entries whose key starts with `prefix`, at most `limit`, sorted by key
+    ///
+    /// When more entries match, backends may return any `limit` of them rather than the first by key.
+    async fn keys(&self, prefix: &str, limit: usize) -> Result<Vec<CacheKeyInfo>>;
+
+    /// Remove every entry whose key starts with `prefix`, returning how many were removed
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize>;
+}
+
+/// One stored cache entry
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct CacheKeyInfo {
+    pub key: String,
+    /// Size of the stored JSON in bytes
+    pub size_bytes: u64,
+    /// Seconds until the entry expires, when known
+    pub ttl_secs: Option<u64>,
+}
+
+/// How a request uses cached results
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CachePolicy {
+    /// Skip cached results and overwrite them with a new scrape
+    pub fresh: bool,
+    /// Only accept cached results stored at most this long ago
+    pub max_age: Option<Duration>,
+}
+
+impl CachePolicy {
+    pub fn new(fresh: bool, max_age_secs: Option<u64>) -> Self {
+        Self {
+            fresh,
+            max_age: max_age_secs.map(Duration::from_secs),
+        }
+    }
+}
+
+/// A cached value together with the time it was stored, so requests can ask for recent data
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedEntry<T> {
+    /// Unix timestamp in seconds
+    pub stored_at: u64,
+    pub value: T,
+}
+
+impl<T> CachedEntry<T> {
+    pub fn new(value: T) -> Self {
+        Self { stored_at: unix_now(), value }
+    }
+
+    /// Time since the entry was stored
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.stored_at))
+    }
+}
+
+/// Current Unix timestamp in seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Create the configured cache backend
+///
+/// `redis` is the shared connection, `None` when Redis did not answer at startup.
+/// With `backend = "auto"`, Redis is used when connected and the in-process LRU
+/// cache otherwise.
+pub fn connect(settings: &CacheSettings, redis: Option<ConnectionManager>) -> Result<Arc<dyn Cache>> {
+    let memory = || -> Arc<dyn Cache> { Arc::new(MemoryCache::new(settings.memory_max_entries)) };
+
+    match (settings.backend, redis) {
+        (CacheBackend::Memory, _) => Ok(memory()),
+        (CacheBackend::Redis | CacheBackend::Auto, Some(connection)) => Ok(Arc::new(RedisCache::new(connection))),
+        (CacheBackend::Redis, None) => bail!("cache.backend is \"redis\" but Redis is unavailable"),
+        (CacheBackend::Auto, None) => {
+            warn!("Caching in memory instead of Redis");
+            Ok(memory())
+        }
+    }
+}
+
+/// Create router for the cache admin endpoints, behind `cache.admin_token`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/scraper/cache", delete(cache_controller::invalidate_handler))
+        .route("/api/scraper/cache/keys", get(cache_controller::keys_handler))
+        .route("/api/scraper/cache/stats", get(cache_controller::stats_handler))
+        .route_layer(middleware::from_fn(require_admin_token))
+}
+
+/// Refuse admin requests without `Authorization: Bearer <cache.admin_token>`, and all of them when no token is set
+async fn require_admin_token(req: Request, next: Next) -> Result<Response, ScraperError> {
+    let token = &settings().cache.admin_token;
+    if token.is_empty() {
+        return Err(ScraperError::Unauthorized(
+            "Cache admin endpoints are disabled, set cache.admin_token to enable them".to_string(),
+        ));
+    }
+    if !is_admin(req.headers(), token) {
+        return Err(ScraperError::Unauthorized("Missing or wrong cache admin token".to_string()));
+    }
+    Ok(next.run(req).await)
+}
+
+/// Whether the request carries `token`, never true when no token is configured
+fn is_admin(headers: &HeaderMap, token: &str) -> bool {
+    if token.is_empty() {
+        return false;
+    }
+    // Compared in constant time so response timing does not reveal the token
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| bool::from(given.trim().as_bytes().ct_eq(token.as_bytes())))
+}
+
+/// Query as it takes part in cache keys: trimmed, lowercased, single spaces
+pub fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Key of one search, e.g. `search:tokopedia:iphone 15:sort=lowest_price:page=2`
+///
+/// Queries differing only in case or whitespace share an entry.
+pub fn search_key(marketplace_id: &str, query: &str, filters: &SearchFilters, page: u32) -> String {
+    let mut key = search_prefix(marketplace_id, Some(query)) + &filters.cache_key_suffix();
+    if page > 1 {
+        key.push_str(&format!(":page={page}"));
+    }
+    key
+}
+
+/// Prefix shared by a marketplace's search keys, narrowed to queries starting with `query`
+pub fn search_prefix(marketplace_id: &str, query: Option<&str>) -> String {
+    format!("search:{marketplace_id}:{}", query.map(normalize_query).unwrap_or_default())
+}
+
+/// Key of one product page, e.g. `product:tokopedia:https://www.tokopedia.com/...`
+pub fn product_key(marketplace_id: &str, url: &str) -> String {
+    product_prefix(marketplace_id) + url.trim()
+}
+
+/// Prefix shared by a marketplace's product keys
+pub fn product_prefix(marketplace_id: &str) -> String {
+    format!("product:{marketplace_id}:")
+}
+
+/// Statistics group of a key: its kind and marketplace, e.g. `search:tokopedia`
+pub fn key_group(key: &str) -> String {
+    key.splitn(3, ':').take(2).collect::<Vec<_>>().join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::marketplace_model::SortOrder;
+
+    #[test]
+    fn test_admin_token_is_required() {
+        let headers = |value: &str| HeaderMap::from_iter([(AUTHORIZATION, value.parse().unwrap())]);
+        assert!(!is_admin(&HeaderMap::new(), ""));
+        assert!(!is_admin(&headers("Bearer "), ""));
+        assert!(is_admin(&headers("Bearer s3cret"), "s3cret"));
+        assert!(!is_admin(&HeaderMap::new(), "s3cret"));
+        assert!(!is_admin(&headers("Bearer wrong"), "s3cret"));
+        assert!(!is_admin(&headers("s3cret"), "s3cret"));
+    }
+
+    #[test]
+    fn test_search_key_normalizes_query() {
+        let filters = SearchFilters::default();
+        assert_eq!(search_key("tokopedia", "iPhone ", &filters, 1), "search:tokopedia:iphone");
+        assert_eq!(
+            search_key("tokopedia", "  iphone   15 Pro", &filters, 1),
+            search_key("tokopedia", "iphone 15 pro", &filters, 1)
+        );
+        assert_ne!(search_key("tokopedia", "iphone", &filters, 1), search_key("blibli", "iphone", &filters, 1));
+    }
+
+    #[test]
+    fn test_prefixes_match_keys() {
+        let key = search_key("tokopedia", "iPhone 15", &SearchFilters::default(), 2);
+        assert!(key.starts_with(&search_prefix("tokopedia", Some("IPHONE"))));
+        assert!(key.starts_with(&search_prefix("tokopedia", None)));
+        assert!(!key.starts_with(&search_prefix("blibli", None)));
+        assert!(product_key("blibli", "https://www.blibli.com/p/1").starts_with(&product_prefix("blibli")));
+        assert_eq!(key_group(&key), "search:tokopedia");
+    }
+
+    #[test]
+    fn test_search_key_includes_filters_and_page() {
+        let filters = SearchFilters {
+            max_price: Some(5_000_000),
+            sort: SortOrder::LowestPrice,
+            ..Default::default()
+        };
+        assert_eq!(
+            search_key("blibli", "laptop", &filters, 3),
+            "search:blibli:laptop:max_price=5000000:sort=lowest_price:page=3"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::cache::{Cache, CacheKeyInfo};
+
+/// Namespace of the scraper's keys, so listing and invalidation leave other apps' keys alone
+const KEY_NAMESPACE: &str = "scraper:";
+
+/// Keys removed per DEL command when invalidating
+const DELETE_BATCH_SIZE: usize = 500;
+
+/// Redis backend sharing one multiplexed connection between all requests
+///
+/// The connection manager reconnects on its own when Redis restarts. Keys are
+/// stored under the `scraper:` namespace.
+pub struct RedisCache {
+    connection: ConnectionManager,
+}
+
+impl RedisCache {
+    /// Cache on the shared connection, see [`crate::redis_client::connect`]
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+
+    /// Stored keys starting with `prefix`, including the namespace, sorted
+    ///
+    /// Stops scanning once `limit` distinct keys are found, so which keys come back
+    /// is up to Redis when more match.
+    async fn scan_prefix(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let pattern = format!("{KEY_NAMESPACE}{}*", escape_glob(prefix));
+        let mut connection = self.connection.clone();
+        let mut iter = connection.scan_match::<_, String>(pattern).await?;
+        // SCAN may return a key more than once
+        let mut keys = BTreeSet::new();
+        while keys.len() < limit {
+            match iter.next_item().await {
+                Some(key) => keys.insert(key),
+                None => break,
+            };
+        }
+        Ok(keys.into_iter().collect())
+    }
+}
+
+/// Escape the characters SCAN MATCH treats as wildcards
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        // Clones share the underlying connection
+        let mut connection = self.connection.clone();
+        Ok(connection.get(format!("{KEY_NAMESPACE}{key}")).await?)
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        let mut connection = self.connection.clone();
+        connection
+            .set_ex::<_, _, ()>(format!("{KEY_NAMESPACE}{key}"), value, ttl.as_secs().max(1))
+            .await?;
+        Ok(())
+    }
+
+    async fn keys(&self, prefix: &str, limit: usize) -> Result<Vec<CacheKeyInfo>> {
+        let keys = self.scan_prefix(prefix, limit).await?;
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.cmd("STRLEN").arg(key).cmd("TTL").arg(key);
+        }
+        let mut connection = self.connection.clone();
+        let sizes: Vec<i64> = pipe.query_async(&mut connection).await?;
+
+        Ok(keys
+            .into_iter()
+            .zip(sizes.chunks(2))
+            .map(|(key, size_and_ttl)| CacheKeyInfo {
+                key: key.strip_prefix(KEY_NAMESPACE).unwrap_or(&key).to_string(),
+                size_bytes: size_and_ttl[0].max(0) as u64,
+                // TTL is negative for keys without expiry or already gone
+                ttl_secs: u64::try_from(size_and_ttl[1]).ok(),
+            })
+            .collect())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<usize> {
+        let keys = self.scan_prefix(prefix, usize::MAX).await?;
+        let mut connection = self.connection.clone();
+        let mut deleted = 0;
+        for batch in keys.chunks(DELETE_BATCH_SIZE) {
+            deleted += connection.del::<_, usize>(batch).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_glob() {
+        assert_eq!(escape_glob("search:tokopedia:iphone"), "search:tokopedia:iphone");
+        assert_eq!(escape_glob("search:blibli:a*b?[c]"), r"search:blibli:a\*b\?\[c\]");
+    }
+}
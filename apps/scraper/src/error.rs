@@ -0,0 +1,205 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::fmt;
+use tracing::{error, warn};
+
+use crate::marketplace::marketplace_dto::ApiResponse;
+
+/// Why a request failed, answered with its own HTTP status and `error_code`
+///
+/// Scraping code raises these inside `anyhow` errors where the cause is known;
+/// [`ScraperError::from`] finds them again at the API boundary, anything else
+/// becomes [`ScraperError::Internal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScraperError {
+    /// Chrome could not be started
+    BrowserLaunch(String),
+    /// The marketplace page did not load in time
+    NavigationTimeout(String),
+    /// The marketplace refused the request, e.g. with a block page or rate limit
+    Blocked(String),
+    /// The search found no products, or the product page holds none
+    NoResults(String),
+    /// The page loaded but its results could not be read
+    ParseFailure(String),
+    /// The cache backend did not answer
+    CacheUnavailable(String),
+    /// The request itself is invalid
+    InvalidRequest(String),
+    /// The request lacks the token an admin endpoint requires
+    Unauthorized(String),
+    /// The requested marketplace, job, watch or alert does not exist
+    NotFound(String),
+    /// Any other failure
+    Internal(String),
+}
+
+impl ScraperError {
+    /// HTTP status answered for this error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BrowserLaunch(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NavigationTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            Self::Blocked(_) => StatusCode::TOO_MANY_REQUESTS,
+            // The URL is fine, but nothing on its page could be extracted
+            Self::NoResults(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::ParseFailure(_) => StatusCode::BAD_GATEWAY,
+            // Like Chrome, a backend the scraper needs is unavailable; `error_code` tells them apart
+            Self::CacheUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine-readable `error_code` of the response envelope
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BrowserLaunch(_) => "browser_launch",
+            Self::NavigationTimeout(_) => "navigation_timeout",
+            Self::Blocked(_) => "blocked",
+            Self::NoResults(_) => "no_results",
+            Self::ParseFailure(_) => "parse_failure",
+            Self::CacheUnavailable(_) => "cache_unavailable",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::NotFound(_) => "not_found",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// The kind whose [`code`](Self::code) is `code`, [`Internal`](Self::Internal) for unknown codes
+    pub fn from_code(code: &str, message: String) -> Self {
+        match code {
+            "browser_launch" => Self::BrowserLaunch(message),
+            "navigation_timeout" => Self::NavigationTimeout(message),
+            "blocked" => Self::Blocked(message),
+            "no_results" => Self::NoResults(message),
+            "parse_failure" => Self::ParseFailure(message),
+            "cache_unavailable" => Self::CacheUnavailable(message),
+            "invalid_request" => Self::InvalidRequest(message),
+            "unauthorized" => Self::Unauthorized(message),
+            "not_found" => Self::NotFound(message),
+            _ => Self::Internal(message),
+        }
+    }
+
+    /// The kind raised anywhere in an `anyhow` error's chain
+    pub fn find(error: &anyhow::Error) -> Option<&ScraperError> {
+        error.chain().find_map(|cause| cause.downcast_ref::<ScraperError>())
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BrowserLaunch(message)
+            | Self::NavigationTimeout(message)
+            | Self::Blocked(message)
+            | Self::NoResults(message)
+            | Self::ParseFailure(message)
+            | Self::CacheUnavailable(message)
+            | Self::InvalidRequest(message)
+            | Self::Unauthorized(message)
+            | Self::NotFound(message)
+            | Self::Internal(message) => message,
+        }
+    }
+
+    /// The same kind of error with another message
+    fn with_message(&self, message: String) -> Self {
+        match self {
+            Self::BrowserLaunch(_) => Self::BrowserLaunch(message),
+            Self::NavigationTimeout(_) => Self::NavigationTimeout(message),
+            Self::Blocked(_) => Self::Blocked(message),
+            Self::NoResults(_) => Self::NoResults(message),
+            Self::ParseFailure(_) => Self::ParseFailure(message),
+            Self::CacheUnavailable(_) => Self::CacheUnavailable(message),
+            Self::InvalidRequest(_) => Self::InvalidRequest(message),
+            Self::Unauthorized(_) => Self::Unauthorized(message),
+            Self::NotFound(_) => Self::NotFound(message),
+            Self::Internal(_) => Self::Internal(message),
+        }
+    }
+}
+
+impl fmt::Display for ScraperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ScraperError {}
+
+impl From<anyhow::Error> for ScraperError {
+    /// The kind raised anywhere in the error's chain, with the whole chain as message
+    fn from(error: anyhow::Error) -> Self {
+        let message = format!("{error:#}");
+        match Self::find(&error) {
+            Some(kind) => kind.with_message(message),
+            None => Self::Internal(message),
+        }
+    }
+}
+
+impl IntoResponse for ScraperError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            error!(error_code = self.code(), "{self}");
+        } else {
+            warn!(error_code = self.code(), "{self}");
+        }
+        (status, Json(ApiResponse::<()>::failure(&self))).into_response()
+    }
+}
+
+/// What every API handler returns: a status and envelope, or an error in the same envelope
+pub type ApiResult<T> = Result<(StatusCode, Json<ApiResponse<T>>), ScraperError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_kind_is_found_through_context() {
+        let error: anyhow::Result<()> = Err(ScraperError::NavigationTimeout("timed out".to_string()).into());
+        let error = ScraperError::from(error.context("Failed to fetch page 2").unwrap_err());
+        assert_eq!(error, ScraperError::NavigationTimeout("Failed to fetch page 2: timed out".to_string()));
+        assert_eq!(error.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(error.code(), "navigation_timeout");
+
+        let error = ScraperError::from(anyhow::anyhow!("disk full"));
+        assert_eq!(error.code(), "internal");
+        assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_every_kind_has_its_own_code_and_status() {
+        let kinds = [
+            "browser_launch",
+            "navigation_timeout",
+            "blocked",
+            "no_results",
+            "parse_failure",
+            "cache_unavailable",
+            "invalid_request",
+            "unauthorized",
+            "not_found",
+            "internal",
+        ]
+        .map(|code| ScraperError::from_code(code, String::new()));
+
+        let codes: std::collections::HashSet<_> = kinds.iter().map(ScraperError::code).collect();
+        let statuses: std::collections::HashSet<_> = kinds.iter().map(ScraperError::status).collect();
+        assert_eq!(codes.len(), kinds.len());
+        // Only the two unavailable backends, Chrome and the cache, share 503
+        assert_eq!(statuses.len(), kinds.len() - 1);
+        assert_eq!(ScraperError::from_code("cache_unavailable", String::new()).status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(ScraperError::from_code("blocked", String::new()).status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(ScraperError::from_code("browser_launch", String::new()).status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{bail, Context, Result};
+use headless_chrome::protocol::cdp::Emulation::{SetDeviceMetricsOverride, SetTouchEmulationEnabled};
+use headless_chrome::Tab;
+use rand::Rng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::{FingerprintRotation, FingerprintSettings};
+
+/// A browser identity whose user agent, platform, language and screen agree with each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FingerprintProfile {
+    /// Name used in `fingerprint.profiles`
+    pub name: &'static str,
+    pub user_agent: &'static str,
+    /// `navigator.platform`
+    pub platform: &'static str,
+    pub accept_language: &'static str,
+    /// Viewport in CSS pixels
+    pub width: u32,
+    pub height: u32,
+    pub device_scale_factor: f64,
+    /// Mobile viewport and touch events, marketplaces answer with their mobile pages
+    pub mobile: bool,
+}
+
+const ACCEPT_LANGUAGE: &str = "id-ID,id;q=0.9,en-US;q=0.8,en;q=0.7";
+
+/// Every built-in profile, desktop ones first
+pub const PROFILES: &[FingerprintProfile] = &[
+    FingerprintProfile {
+        name: "windows_chrome",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "Win32",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 1920,
+        height: 1080,
+        device_scale_factor: 1.0,
+        mobile: false,
+    },
+    FingerprintProfile {
+        name: "windows_edge",
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
+        platform: "Win32",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 1366,
+        height: 768,
+        device_scale_factor: 1.0,
+        mobile: false,
+    },
+    FingerprintProfile {
+        name: "macos_chrome",
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "MacIntel",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 1440,
+        height: 900,
+        device_scale_factor: 2.0,
+        mobile: false,
+    },
+    FingerprintProfile {
+        name: "linux_chrome",
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "Linux x86_64",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 1920,
+        height: 1080,
+        device_scale_factor: 1.0,
+        mobile: false,
+    },
+    FingerprintProfile {
+        name: "android_pixel",
+        user_agent: "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Mobile Safari/537.36",
+        platform: "Linux armv81",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 412,
+        height: 915,
+        device_scale_factor: 2.625,
+        mobile: true,
+    },
+    FingerprintProfile {
+        name: "android_samsung",
+        user_agent: "Mozilla/5.0 (Linux; Android 13; SM-A546E) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Mobile Safari/537.36",
+        platform: "Linux armv81",
+        accept_language: ACCEPT_LANGUAGE,
+        width: 384,
+        height: 854,
+        device_scale_factor: 2.8125,
+        mobile: true,
+    },
+];
+
+impl FingerprintProfile {
+    pub fn find(name: &str) -> Option<&'static Self> {
+        PROFILES.iter().find(|profile| profile.name == name)
+    }
+
+    /// Make the tab present this profile from its next navigation on
+    ///
+    /// Every override is set, so a reused tab keeps nothing of its previous profile.
+    pub fn apply(&self, tab: &Tab) -> Result<()> {
+        tab.set_user_agent(self.user_agent, Some(self.accept_language), Some(self.platform))
+            .context("Failed to override user agent")?;
+        tab.call_method(SetDeviceMetricsOverride {
+            width: self.width,
+            height: self.height,
+            device_scale_factor: self.device_scale_factor,
+            mobile: self.mobile,
+            scale: None,
+            screen_width: None,
+            screen_height: None,
+            position_x: None,
+            position_y: None,
+            dont_set_visible_size: None,
+            screen_orientation: None,
+            viewport: None,
+            display_feature: None,
+            device_posture: None,
+        })
+        .context("Failed to override device metrics")?;
+        tab.call_method(SetTouchEmulationEnabled {
+            enabled: self.mobile,
+            max_touch_points: self.mobile.then_some(5),
+        })
+        .context("Failed to override touch support")?;
+        Ok(())
+    }
+}
+
+/// The profiles of `fingerprint.profiles`, handed out per page as `fingerprint.rotation` says
+pub struct Fingerprints {
+    profiles: Vec<&'static FingerprintProfile>,
+    rotation: FingerprintRotation,
+    next: AtomicUsize,
+}
+
+impl Fingerprints {
+    pub fn new(settings: &FingerprintSettings) -> Result<Self> {
+        let profiles = settings
+            .profiles
+            .iter()
+            .map(|name| FingerprintProfile::find(name).with_context(|| format!("Unknown fingerprint profile '{name}'")))
+            .collect::<Result<Vec<_>>>()?;
+        if profiles.is_empty() && settings.rotation != FingerprintRotation::Off {
+            bail!("fingerprint.profiles must not be empty unless fingerprint.rotation is \"off\"");
+        }
+        Ok(Self { profiles, rotation: settings.rotation, next: AtomicUsize::new(0) })
+    }
+
+    /// Profile of the next page, `None` when pages keep the browser's own identity
+    pub fn next(&self) -> Option<&'static FingerprintProfile> {
+        let index = match self.rotation {
+            FingerprintRotation::Off => return None,
+            FingerprintRotation::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+            FingerprintRotation::Random => rand::thread_rng().gen_range(0..self.profiles.len()),
+        };
+        Some(self.profiles[index % self.profiles.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles_rotate_in_order_or_at_random() {
+        let settings = |rotation| FingerprintSettings {
+            rotation,
+            profiles: vec!["windows_chrome".to_string(), "android_pixel".to_string()],
+        };
+
+        let fingerprints = Fingerprints::new(&settings(FingerprintRotation::RoundRobin)).unwrap();
+        let names: Vec<&str> = (0..3).map(|_| fingerprints.next().unwrap().name).collect();
+        assert_eq!(names, ["windows_chrome", "android_pixel", "windows_chrome"]);
+
+        let fingerprints = Fingerprints::new(&settings(FingerprintRotation::Random)).unwrap();
+        assert!((0..20).all(|_| ["windows_chrome", "android_pixel"].contains(&fingerprints.next().unwrap().name)));
+        assert!(Fingerprints::new(&settings(FingerprintRotation::Off)).unwrap().next().is_none());
+
+        let unknown = FingerprintSettings { profiles: vec!["nokia_3310".to_string()], ..settings(FingerprintRotation::Random) };
+        assert!(Fingerprints::new(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_profiles_are_consistent() {
+        for profile in PROFILES {
+            assert_eq!(profile.mobile, profile.user_agent.contains("Mobile"), "{}", profile.name);
+            assert_eq!(profile.mobile, profile.width < profile.height, "{}", profile.name);
+            let windows = profile.user_agent.contains("Windows");
+            assert_eq!(windows, profile.platform == "Win32", "{}", profile.name);
+        }
+    }
+}
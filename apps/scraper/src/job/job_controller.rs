@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use tracing::info;
+
+use crate::config::settings;
+use crate::error::{ApiResult, ScraperError};
+use crate::job::job_dto::{CreateJobRequest, CreateJobResult};
+use crate::job::job_model::Job;
+use crate::marketplace::marketplace_controller::ApiResponseError;
+use crate::marketplace::marketplace_dto::{ApiResponse, SearchRequest};
+use crate::outbound_url::check_outbound_url;
+use crate::state::AppState;
+
+/// HTTP handler starting a search in the background
+#[utoipa::path(
+    post,
+    path = "/api/scraper/jobs",
+    request_body = CreateJobRequest,
+    responses(
+        (status = 202, description = "Job queued, poll `status_url` for progress and results", body = ApiResponse<CreateJobResult>),
+        (status = 400, description = "Unknown marketplace in `sources`, invalid limit or filters, or a `callback_url` that is invalid or internal", body = inline(ApiResponseError)),
+        (status = 500, description = "Job could not be stored", body = inline(ApiResponseError))
+    ),
+    tag = "jobs"
+)]
+pub async fn create_job_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CreateJobRequest>,
+) -> ApiResult<CreateJobResult> {
+    info!(
+        "Received job request: sources={:?}, query='{}', limit={}, pages={:?}",
+        body.search.sources, body.search.query, body.search.limit, body.search.pages
+    );
+
+    validate(&state, &body).await.map_err(ScraperError::InvalidRequest)?;
+
+    let job = state
+        .jobs
+        .submit(&state, body.search, body.callback_url)
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to store job: {e}")))?;
+    let result = CreateJobResult {
+        status_url: format!("/api/scraper/jobs/{}", job.id),
+        id: job.id,
+        status: job.status,
+    };
+    Ok((StatusCode::ACCEPTED, Json(ApiResponse::success(result, 1))))
+}
+
+/// Reject jobs that could only fail, before they are queued
+async fn validate(state: &AppState, body: &CreateJobRequest) -> Result<(), String> {
+    state
+        .marketplaces
+        .resolve(body.search.sources.as_deref())
+        .map_err(|e| e.to_string())?;
+    SearchRequest::from(body.search.clone())
+        .validate()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(callback_url) = &body.callback_url {
+        check_outbound_url(callback_url, &settings().jobs.callback_allowed_hosts)
+            .await
+            .map_err(|e| format!("callback_url: {e}"))?;
+    }
+    Ok(())
+}
+
+/// HTTP handler reporting a job's status, progress and results
+#[utoipa::path(
+    get,
+    path = "/api/scraper/jobs/{id}",
+    params(("id" = String, Path, description = "Job identifier returned when the job was created")),
+    responses(
+        (status = 200, description = "Job status and progress, with the results once done", body = ApiResponse<Job>),
+        (status = 404, description = "Unknown or expired job", body = inline(ApiResponseError)),
+        (status = 500, description = "Job store error", body = inline(ApiResponseError))
+    ),
+    tag = "jobs"
+)]
+pub async fn get_job_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<Job> {
+    match state.jobs.get(&id).await {
+        Ok(Some(job)) => Ok((StatusCode::OK, Json(ApiResponse::success(job, 1)))),
+        Ok(None) => Err(ScraperError::NotFound(format!("Unknown job '{id}'"))),
+        Err(e) => Err(ScraperError::Internal(format!("Failed to read job {id}: {e}"))),
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::job::job_model::JobStatus;
+use crate::marketplace::marketplace_dto::AggregatedSearchQuery;
+
+/// Body of `POST /api/scraper/jobs`: the parameters of `GET /api/scraper/search` plus a callback
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateJobRequest {
+    #[serde(flatten)]
+    pub search: AggregatedSearchQuery,
+    /// URL receiving a POST with the job as JSON once it is done or failed
+    pub callback_url: Option<String>,
+}
+
+/// A job that was accepted
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateJobResult {
+    /// Job identifier
+    pub id: String,
+    pub status: JobStatus,
+    /// Where the job's status and results can be polled
+    pub status_url: String,
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+use crate::marketplace::marketplace_dto::{AggregatedSearchQuery, AggregatedSearchResult};
+
+/// Lifecycle of a scrape job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for a free job slot
+    Queued,
+    Running,
+    /// At least one marketplace returned results
+    Done,
+    /// Every marketplace failed
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed)
+    }
+}
+
+/// Pages and products one marketplace has scraped so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SourceProgress {
+    /// Result pages fetched, in order
+    pub pages_fetched: Vec<u32>,
+    /// Products collected
+    pub products: usize,
+}
+
+/// How far a job has come, over every marketplace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct JobProgress {
+    /// Result pages fetched so far
+    pub pages_fetched: usize,
+    /// Products collected so far
+    pub products: usize,
+    /// Progress per marketplace id
+    pub sources: BTreeMap<String, SourceProgress>,
+}
+
+impl JobProgress {
+    /// Count a scraped result page of `source`, which has now collected `products`
+    pub fn record_page(&mut self, source: &str, page: u32, products: usize) {
+        let progress = self.sources.entry(source.to_string()).or_default();
+        progress.pages_fetched.push(page);
+        progress.products = products;
+        self.update_totals();
+    }
+
+    /// Final progress of a finished search, including marketplaces answered from the cache
+    pub fn from_result(result: &AggregatedSearchResult) -> Self {
+        let mut progress = JobProgress::default();
+        for (source, pages) in &result.pages_fetched {
+            progress.sources.insert(
+                source.clone(),
+                SourceProgress {
+                    pages_fetched: pages.clone(),
+                    products: result.products.iter().filter(|p| &p.source == source).count(),
+                },
+            );
+        }
+        progress.update_totals();
+        progress
+    }
+
+    fn update_totals(&mut self) {
+        self.pages_fetched = self.sources.values().map(|source| source.pages_fetched.len()).sum();
+        self.products = self.sources.values().map(|source| source.products).sum();
+    }
+}
+
+/// Delivery of the completion callback
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CallbackOutcome {
+    /// Whether the callback URL answered with a 2xx status
+    pub delivered: bool,
+    /// Requests made
+    pub attempts: u32,
+    /// HTTP status of the last response
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub status_code: Option<u16>,
+    /// Why the last request failed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+}
+
+/// A search scraped in the background, polled through `GET /api/scraper/jobs/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    /// Search parameters, as accepted by `GET /api/scraper/search`
+    pub params: AggregatedSearchQuery,
+    /// URL receiving a POST with the job once it finished
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub callback_url: Option<String>,
+    pub progress: JobProgress,
+    /// Merged results, once the job finished
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub result: Option<AggregatedSearchResult>,
+    /// Why the job failed
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+    /// Delivery of the completion callback, once attempted
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub callback: Option<CallbackOutcome>,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub started_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub finished_at: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::marketplace_model::Product;
+
+    fn product(source: &str) -> Product {
+        Product {
+            name: "iPhone 15".to_string(),
+            price: "Rp13.499.000".to_string(),
+            price_idr: 13_499_000,
+            original_price_idr: None,
+            discount_percent: None,
+            rating: None,
+            review_count: None,
+            image_url: String::new(),
+            product_url: String::new(),
+            shop_location: None,
+            sold: None,
+            sold_min: None,
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_progress_totals_follow_pages_and_results() {
+        let mut progress = JobProgress::default();
+        progress.record_page("tokopedia", 1, 60);
+        progress.record_page("blibli", 1, 40);
+        progress.record_page("tokopedia", 2, 110);
+        assert_eq!((progress.pages_fetched, progress.products), (3, 150));
+        assert_eq!(progress.sources["tokopedia"].pages_fetched, vec![1, 2]);
+
+        // Marketplaces answered from the cache only show up in the final result
+        let result = AggregatedSearchResult {
+            products: vec![product("tokopedia"), product("tokopedia"), product("blibli")],
+            errors: Vec::new(),
+            pages_fetched: [("tokopedia".to_string(), vec![1, 2]), ("blibli".to_string(), vec![1])]
+                .into_iter()
+                .collect(),
+        };
+        let progress = JobProgress::from_result(&result);
+        assert_eq!((progress.pages_fetched, progress.products), (3, 3));
+        assert_eq!(progress.sources["blibli"].products, 1);
+    }
+}
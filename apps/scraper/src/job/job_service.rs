@@ -0,0 +1,357 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use uuid::Uuid;
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::cache::unix_now;
+use crate::config::settings;
+use crate::job::job_model::{CallbackOutcome, Job, JobProgress, JobStatus};
+use crate::job::JobStore;
+use crate::marketplace::marketplace_dto::{AggregatedSearchQuery, SearchRequest};
+use crate::marketplace::marketplace_service::{MarketplaceService, PageProgress};
+use crate::outbound_url::check_outbound_url;
+use crate::state::AppState;
+
+/// Scrape jobs running in the background, at most `jobs.max_concurrent` at a time
+///
+/// Every change of a job is written to the store, so polling clients see its
+/// progress and a restarted scraper can pick up the jobs that were interrupted.
+pub struct JobQueue {
+    store: Arc<dyn JobStore>,
+    slots: Semaphore,
+    /// Hosts callbacks may go to although they resolve to an internal address
+    callback_allowed_hosts: Vec<String>,
+}
+
+impl JobQueue {
+    pub fn new(store: Arc<dyn JobStore>) -> Self {
+        Self {
+            store,
+            slots: Semaphore::new(settings().jobs.max_concurrent),
+            callback_allowed_hosts: settings().jobs.callback_allowed_hosts.clone(),
+        }
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Job>> {
+        self.store.get(id).await
+    }
+
+    /// Store a new job and run it in the background
+    pub async fn submit(
+        &self,
+        state: &AppState,
+        params: AggregatedSearchQuery,
+        callback_url: Option<String>,
+    ) -> Result<Job> {
+        let job = Job {
+            id: Uuid::new_v4().to_string(),
+            status: JobStatus::Queued,
+            params,
+            callback_url,
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            callback: None,
+            created_at: unix_now(),
+            started_at: None,
+            finished_at: None,
+        };
+        self.save(&job).await?;
+        info!("Job {} queued: query='{}'", job.id, job.params.query);
+
+        spawn_run(state.clone(), job.clone());
+        Ok(job)
+    }
+
+    /// Run again the jobs a previous run of the scraper left queued or running
+    ///
+    /// Interrupted jobs start over from their first page.
+    pub async fn resume(&self, state: &AppState) -> Result<usize> {
+        let jobs = self.store.unfinished().await?;
+        let count = jobs.len();
+        for mut job in jobs {
+            info!("Resuming job {} ({:?} before the restart)", job.id, job.status);
+            job.status = JobStatus::Queued;
+            job.progress = JobProgress::default();
+            job.started_at = None;
+            self.update(&job).await;
+            spawn_run(state.clone(), job);
+        }
+        Ok(count)
+    }
+
+    async fn save(&self, job: &Job) -> Result<()> {
+        self.store.save(job, Duration::from_secs(settings().jobs.ttl_secs)).await
+    }
+
+    /// Save a job the scrape goes on with even when the store fails
+    async fn update(&self, job: &Job) {
+        if let Err(e) = self.save(job).await {
+            warn!("Failed to save job {} to {}: {e}", job.id, self.store.name());
+        }
+    }
+}
+
+/// Run a job in the background, logged under the request that created it if any
+fn spawn_run(state: AppState, job: Job) {
+    let span = info_span!("job", job_id = %job.id);
+    tokio::spawn(async move { run(&state, job).await }.instrument(span));
+}
+
+/// Wait for a job slot, scrape the job's search while recording its progress,
+/// then store the results and call the callback URL
+async fn run(state: &AppState, mut job: Job) {
+    let queue = &state.jobs;
+    let Ok(slot) = queue.slots.acquire().await else {
+        return;
+    };
+
+    job.status = JobStatus::Running;
+    job.started_at = Some(unix_now());
+    queue.update(&job).await;
+    info!("Job {} started", job.id);
+
+    match state.marketplaces.resolve(job.params.sources.as_deref()) {
+        Ok(marketplaces) => {
+            let sources = marketplaces.len();
+            let request = SearchRequest::from(job.params.clone());
+
+            // Pages are reported from the scraping tasks, the job is saved here
+            let (pages_tx, mut pages_rx) = mpsc::unbounded_channel();
+            let on_page: PageProgress = Arc::new(move |source: &str, page, products| {
+                let _ = pages_tx.send((source.to_string(), page, products));
+            });
+            let search = MarketplaceService::search_all(marketplaces, state, &request, Some(on_page));
+            tokio::pin!(search);
+            let result = loop {
+                tokio::select! {
+                    result = &mut search => break result,
+                    Some((source, page, products)) = pages_rx.recv() => {
+                        job.progress.record_page(&source, page, products);
+                        queue.update(&job).await;
+                    }
+                }
+            };
+
+            job.progress = JobProgress::from_result(&result);
+            if result.errors.len() == sources {
+                job.status = JobStatus::Failed;
+                job.error = Some(
+                    result
+                        .errors
+                        .iter()
+                        .map(|e| format!("{}: {}", e.source, e.error))
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                );
+            } else {
+                job.status = JobStatus::Done;
+            }
+            job.result = Some(result);
+        }
+        Err(e) => {
+            job.status = JobStatus::Failed;
+            job.error = Some(e.to_string());
+        }
+    }
+    job.finished_at = Some(unix_now());
+    queue.update(&job).await;
+    drop(slot);
+
+    match &job.error {
+        None => info!("Job {} done: {} products", job.id, job.progress.products),
+        Some(error) => error!("Job {} failed: {error}", job.id),
+    }
+
+    if let Some(url) = job.callback_url.clone() {
+        job.callback = Some(send_callback(&url, &job, &queue.callback_allowed_hosts).await);
+        queue.update(&job).await;
+    }
+}
+
+/// POST the finished job to its callback URL, retrying with a growing delay
+///
+/// The URL is checked again right before sending, since the job may have been
+/// resumed under other settings or the host may resolve elsewhere by now.
+async fn send_callback(url: &str, job: &Job, allowed_hosts: &[String]) -> CallbackOutcome {
+    let jobs = &settings().jobs;
+    let mut outcome = CallbackOutcome::default();
+
+    let checked = match check_outbound_url(url, allowed_hosts).await {
+        Ok(checked) => checked,
+        Err(e) => {
+            error!("Job {} callback refused: {e}", job.id);
+            outcome.error = Some(format!("Callback refused: {e}"));
+            return outcome;
+        }
+    };
+    // Connecting to the checked addresses and not following redirects keeps
+    // the callback on the host it was checked against
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(jobs.callback_timeout_secs))
+        .redirect(reqwest::redirect::Policy::none());
+    let client = match checked.pin(builder).build() {
+        Ok(client) => client,
+        Err(e) => {
+            outcome.error = Some(format!("Failed to create HTTP client: {e}"));
+            return outcome;
+        }
+    };
+    let body = match serde_json::to_string(job) {
+        Ok(body) => body,
+        Err(e) => {
+            outcome.error = Some(format!("Failed to encode job: {e}"));
+            return outcome;
+        }
+    };
+
+    for attempt in 1..=jobs.callback_attempts {
+        outcome.attempts = attempt;
+        let response = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => {
+                info!("Job {} callback delivered to {url}", job.id);
+                outcome.delivered = true;
+                outcome.status_code = Some(response.status().as_u16());
+                outcome.error = None;
+                return outcome;
+            }
+            Ok(response) => {
+                outcome.status_code = Some(response.status().as_u16());
+                outcome.error = Some(format!("{url} responded with HTTP {}", response.status()));
+            }
+            Err(e) => {
+                outcome.status_code = None;
+                outcome.error = Some(e.to_string());
+            }
+        }
+
+        if attempt < jobs.callback_attempts {
+            let delay = Duration::from_secs(1 << (attempt - 1).min(6));
+            warn!("Job {} callback attempt {attempt} failed, retrying in {delay:?}", job.id);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    error!(
+        "Job {} callback to {url} failed: {}",
+        job.id,
+        outcome.error.as_deref().unwrap_or_default()
+    );
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::MemoryJobStore;
+    use crate::page_source::test_support::search_page_state;
+    use axum::{routing::post, Json, Router};
+    use std::sync::Mutex;
+
+    fn params(body: serde_json::Value) -> AggregatedSearchQuery {
+        serde_json::from_value(body).unwrap()
+    }
+
+    /// Poll the store until the job finished
+    async fn finished_job(state: &AppState, id: &str) -> Job {
+        for _ in 0..200 {
+            let job = state.jobs.get(id).await.unwrap().unwrap();
+            if job.status.is_finished() && (job.callback_url.is_none() || job.callback.is_some()) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_in_background_and_calls_back() {
+        // Callback receiver on a free local port
+        let received = Arc::new(Mutex::new(Vec::<Job>::new()));
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/done",
+            post(move |Json(job): Json<Job>| async move { sink.lock().unwrap().push(job) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let callback_url = format!("http://{}/done", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut state = search_page_state();
+        state.jobs = Arc::new(JobQueue {
+            callback_allowed_hosts: vec!["127.0.0.1".to_string()],
+            ..JobQueue::new(Arc::new(MemoryJobStore::default()))
+        });
+        let search = params(serde_json::json!({ "query": "iphone", "limit": 2, "pages": 1 }));
+        let job = state.jobs.submit(&state, search, Some(callback_url)).await.unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+
+        let job = finished_job(&state, &job.id).await;
+        assert_eq!(job.status, JobStatus::Done);
+        assert_eq!(job.result.as_ref().unwrap().products.len(), 2);
+        assert_eq!(job.progress.pages_fetched, 1);
+        assert_eq!(job.progress.sources["tokopedia"].pages_fetched, vec![1]);
+        let callback = job.callback.unwrap();
+        assert!(callback.delivered, "{callback:?}");
+        assert_eq!(callback.attempts, 1);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].id, job.id);
+        assert_eq!(received[0].status, JobStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_jobs_are_resumed() {
+        let state = search_page_state();
+        let interrupted = Job {
+            id: "interrupted".to_string(),
+            status: JobStatus::Running,
+            params: params(serde_json::json!({ "sources": "tokopedia", "query": "iphone", "limit": 1 })),
+            callback_url: None,
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            callback: None,
+            created_at: unix_now(),
+            started_at: Some(unix_now()),
+            finished_at: None,
+        };
+        state.jobs.save(&interrupted).await.unwrap();
+
+        assert_eq!(state.jobs.resume(&state).await.unwrap(), 1);
+        let job = finished_job(&state, "interrupted").await;
+        assert_eq!(job.status, JobStatus::Done);
+        assert_eq!(job.result.unwrap().products.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_callback_to_internal_address_is_refused_when_sent() {
+        let job = Job {
+            id: "finished".to_string(),
+            status: JobStatus::Done,
+            params: params(serde_json::json!({ "query": "iphone" })),
+            callback_url: Some("http://127.0.0.1:9/done".to_string()),
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            callback: None,
+            created_at: unix_now(),
+            started_at: Some(unix_now()),
+            finished_at: Some(unix_now()),
+        };
+
+        let outcome = send_callback("http://127.0.0.1:9/done", &job, &[]).await;
+        assert!(!outcome.delivered);
+        assert_eq!(outcome.attempts, 0);
+        assert!(outcome.error.unwrap().contains("internal address"));
+    }
+}
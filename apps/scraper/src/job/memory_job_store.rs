@@ -0,0 +1,113 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::job::job_model::Job;
+use crate::job::JobStore;
+
+/// In-process job store, used when Redis is not available
+///
+/// Jobs are lost on restart and only visible to the instance that accepted them.
+#[derive(Default)]
+pub struct MemoryJobStore {
+    jobs: Mutex<HashMap<String, StoredJob>>,
+}
+
+struct StoredJob {
+    job: Job,
+    /// `None` when the TTL reaches past what `Instant` can represent
+    expires_at: Option<Instant>,
+}
+
+impl StoredJob {
+    fn is_live(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[async_trait]
+impl JobStore for MemoryJobStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn save(&self, job: &Job, ttl: Duration) -> Result<()> {
+        let now = Instant::now();
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        // Expired jobs are dropped here, nothing else would free them
+        jobs.retain(|_, stored| stored.is_live(now));
+        jobs.insert(job.id.clone(), StoredJob { job: job.clone(), expires_at: now.checked_add(ttl) });
+        Ok(())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Job>> {
+        let jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(jobs
+            .get(id)
+            .filter(|stored| stored.is_live(Instant::now()))
+            .map(|stored| stored.job.clone()))
+    }
+
+    async fn unfinished(&self) -> Result<Vec<Job>> {
+        let jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut unfinished: Vec<Job> = jobs
+            .values()
+            .filter(|stored| stored.is_live(Instant::now()) && !stored.job.status.is_finished())
+            .map(|stored| stored.job.clone())
+            .collect();
+        unfinished.sort_by_key(|job| job.created_at);
+        Ok(unfinished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::job_model::{JobProgress, JobStatus};
+
+    fn job(id: &str, status: JobStatus, created_at: u64) -> Job {
+        Job {
+            id: id.to_string(),
+            status,
+            params: serde_json::from_str(r#"{"query": "iphone"}"#).unwrap(),
+            callback_url: None,
+            progress: JobProgress::default(),
+            result: None,
+            error: None,
+            callback: None,
+            created_at,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unfinished_jobs_oldest_first() {
+        let store = MemoryJobStore::default();
+        let ttl = Duration::from_secs(60);
+        store.save(&job("b", JobStatus::Running, 2), ttl).await.unwrap();
+        store.save(&job("a", JobStatus::Queued, 1), ttl).await.unwrap();
+        store.save(&job("c", JobStatus::Done, 3), ttl).await.unwrap();
+
+        let ids: Vec<String> = store.unfinished().await.unwrap().into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(store.get("c").await.unwrap().unwrap().status, JobStatus::Done);
+        assert!(store.get("d").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_jobs_are_gone() {
+        let store = MemoryJobStore::default();
+        store.save(&job("a", JobStatus::Done, 1), Duration::ZERO).await.unwrap();
+        assert!(store.get("a").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_huge_ttl_never_expires() {
+        let store = MemoryJobStore::default();
+        store.save(&job("a", JobStatus::Done, 1), Duration::from_secs(u64::MAX)).await.unwrap();
+        assert!(store.get("a").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,55 @@
+pub mod job_controller;
+pub mod job_dto;
+pub mod job_model;
+pub mod job_service;
+pub mod memory_job_store;
+pub mod redis_job_store;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{routing::{get, post}, Router};
+use redis::aio::ConnectionManager;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::job::job_model::Job;
+use crate::state::AppState;
+
+pub use job_service::JobQueue;
+pub use memory_job_store::MemoryJobStore;
+pub use redis_job_store::RedisJobStore;
+
+/// Where scrape jobs and their results are kept between polls
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// Short name used in logs, e.g. `redis`
+    fn name(&self) -> &'static str;
+
+    /// Insert or replace a job, dropping it `ttl` after this update
+    async fn save(&self, job: &Job, ttl: Duration) -> Result<()>;
+
+    /// Job with this id, `None` when unknown or expired
+    async fn get(&self, id: &str) -> Result<Option<Job>>;
+
+    /// Jobs still queued or running, oldest first
+    async fn unfinished(&self) -> Result<Vec<Job>>;
+}
+
+/// Job store on the shared Redis connection, in-process when Redis is unavailable
+pub fn store(redis: Option<ConnectionManager>) -> Arc<dyn JobStore> {
+    match redis {
+        Some(connection) => Arc::new(RedisJobStore::new(connection)),
+        None => {
+            warn!("Keeping jobs in memory instead of Redis, they are lost on restart");
+            Arc::new(MemoryJobStore::default())
+        }
+    }
+}
+
+/// Create router for the asynchronous job endpoints
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/scraper/jobs", post(job_controller::create_job_handler))
+        .route("/api/scraper/jobs/:id", get(job_controller::get_job_handler))
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::time::Duration;
+use tracing::{instrument, warn};
+
+use crate::job::job_model::Job;
+use crate::job::JobStore;
+
+/// Namespace of job keys, kept apart from the cache's `scraper:` keys so
+/// invalidating the whole cache leaves jobs alone
+const KEY_NAMESPACE: &str = "scraper-job:";
+
+/// Jobs stored as JSON in Redis, so they survive a restart of the scraper
+pub struct RedisJobStore {
+    connection: ConnectionManager,
+}
+
+impl RedisJobStore {
+    /// Job store on the shared connection, see [`crate::redis_client::connect`]
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self { connection }
+    }
+}
+
+fn job_key(id: &str) -> String {
+    format!("{KEY_NAMESPACE}{id}")
+}
+
+#[async_trait]
+impl JobStore for RedisJobStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    #[instrument(name = "redis_job_save", level = "debug", skip_all, fields(job_id = %job.id))]
+    async fn save(&self, job: &Job, ttl: Duration) -> Result<()> {
+        let json = serde_json::to_string(job)?;
+        let mut connection = self.connection.clone();
+        connection
+            .set_ex::<_, _, ()>(job_key(&job.id), json, ttl.as_secs().max(1))
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "redis_job_get", level = "debug", skip(self))]
+    async fn get(&self, id: &str) -> Result<Option<Job>> {
+        let mut connection = self.connection.clone();
+        let json: Option<String> = connection.get(job_key(id)).await?;
+        json.map(|json| serde_json::from_str(&json).with_context(|| format!("Stored job {id} is invalid")))
+            .transpose()
+    }
+
+    async fn unfinished(&self) -> Result<Vec<Job>> {
+        let mut connection = self.connection.clone();
+        let mut keys = Vec::new();
+        {
+            let mut iter = connection.scan_match::<_, String>(format!("{KEY_NAMESPACE}*")).await?;
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+        }
+
+        let mut unfinished = Vec::new();
+        for key in keys {
+            let Some(json) = connection.get::<_, Option<String>>(&key).await? else {
+                continue;
+            };
+            match serde_json::from_str::<Job>(&json) {
+                Ok(job) if !job.status.is_finished() => unfinished.push(job),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping invalid stored job {key}: {e}"),
+            }
+        }
+        unfinished.sort_by_key(|job| job.created_at);
+        Ok(unfinished)
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+use axum::http::HeaderMap;
+use std::io::IsTerminal;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+use crate::config::{LogFormat, LoggingSettings, LOG_LEVEL};
+
+/// Header carrying the request id, taken from the client when usable and echoed in the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id accepted from a client
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Event filter of `logging.level`
+pub fn env_filter(level: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(level).map_err(|e| anyhow!("invalid filter '{level}': {e}"))
+}
+
+/// Send every log event to stdout, as text or JSON lines
+///
+/// Events carry the fields of the spans they happen in, e.g. the `request_id`
+/// of the request span opened by the logging middleware.
+pub fn init(settings: &LoggingSettings) {
+    let filter = env_filter(&settings.level).unwrap_or_else(|_| EnvFilter::new(LOG_LEVEL));
+    // Colors only when a person reads the output
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(std::io::stdout().is_terminal());
+    let result = match settings.format {
+        LogFormat::Text => subscriber.try_init(),
+        LogFormat::Json => subscriber.json().flatten_event(true).try_init(),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to initialize logging: {e}");
+    }
+}
+
+/// The client's `X-Request-Id`, or a new id when it is missing or unusable
+pub fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic()))
+        .map_or_else(|| Uuid::new_v4().to_string(), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_id_is_propagated_or_generated() {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "gateway-42".parse().unwrap());
+        assert_eq!(request_id(&headers), "gateway-42");
+
+        // Missing, blank or oversized ids are replaced by a UUID
+        headers.insert(REQUEST_ID_HEADER, " ".parse().unwrap());
+        assert!(Uuid::parse_str(&request_id(&headers)).is_ok());
+        headers.insert(REQUEST_ID_HEADER, "x".repeat(MAX_REQUEST_ID_LEN + 1).parse().unwrap());
+        assert!(Uuid::parse_str(&request_id(&headers)).is_ok());
+        assert_ne!(request_id(&HeaderMap::new()), request_id(&HeaderMap::new()));
+    }
+}
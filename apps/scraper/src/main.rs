@@ -1,29 +1,79 @@
+mod alert;
 mod blibli;
-use crate::blibli::blibli_model::BlibliProduct;
+mod block_detection;
 mod browser;
+mod browser_pool;
+mod cache;
 mod config;
+mod error;
+mod fingerprint;
+mod job;
+mod logging;
+mod marketplace;
+mod metrics;
+mod outbound_url;
+mod page_source;
+#[cfg(test)]
+mod parser_fixtures;
+mod popularity;
+mod pricing;
+mod product_detail;
+mod proxy_pool;
+mod redis_client;
+mod state;
 mod tokopedia;
+mod watch;
 // mod shopee;
 
-use axum::{Router, extract::Request, middleware::{self, Next}, response::Response};
+use axum::{Router, extract::{MatchedPath, Request}, http::HeaderValue, middleware::{self, Next}, response::Response};
+use clap::Parser;
 use tower_http::cors::{Any, CorsLayer};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
+use std::sync::Arc;
 use std::time::Instant;
+use tracing::{error, info, info_span, warn, Instrument};
 
-use crate::config::{SERVER_HOST, SERVER_PORT};
-use crate::tokopedia::tokopedia_model::Product;
+use crate::blibli::BlibliMarketplace;
+use crate::browser_pool::BrowserPool;
+use crate::config::{Cli, PageSourceKind, Settings};
+use crate::fingerprint::Fingerprints;
+use crate::logging::REQUEST_ID_HEADER;
+use crate::marketplace::marketplace_model::Product;
+use crate::marketplace::marketplace_registry::MarketplaceRegistry;
+use crate::metrics::metrics;
+use crate::page_source::{ChromePageSource, FixturePageSource, HttpPageSource, PageSource, PageSources};
+use crate::proxy_pool::ProxyPool;
+use crate::state::AppState;
+use crate::tokopedia::TokopediaMarketplace;
 // use crate::shopee::shopee_model::ShopeeProduct;
 
+/// Run every request in a span carrying its request id, echoed in `X-Request-Id`
 async fn logging_middleware(req: Request, next: Next) -> Response {
     let start = Instant::now();
     let method = req.method().clone();
     let uri = req.uri().clone();
+    // Label by route template so ids and queries don't explode the label set
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+    let request_id = logging::request_id(req.headers());
+    // `count` is recorded by `ApiResponse::success`
+    let span = info_span!("request", %request_id, %method, %uri, count = tracing::field::Empty);
     
-    let response = next.run(req).await;
+    let mut response = next.run(req).instrument(span.clone()).await;
     
     let elapsed = start.elapsed();
-    println!("⏱️  API Response: {method} {uri} - {elapsed:?}");
+    let status = response.status();
+    span.in_scope(|| info!(status = status.as_u16(), elapsed_ms = elapsed.as_millis() as u64, "API response"));
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    metrics()
+        .http_requests
+        .with_label_values(&[method.as_str(), &route, status.as_str()])
+        .observe(elapsed.as_secs_f64());
     
     response
 }
@@ -31,50 +81,231 @@ async fn logging_middleware(req: Request, next: Next) -> Response {
 #[derive(OpenApi)]
 #[openapi(
     paths(
-        tokopedia::tokopedia_controller::tokopedia_handler,
-        blibli::blibli_controller::blibli_handler
+        marketplace::marketplace_controller::aggregated_search_handler,
+        marketplace::marketplace_controller::product_detail_handler,
+        marketplace::marketplace_controller::search_handler,
+        cache::cache_controller::keys_handler,
+        cache::cache_controller::invalidate_handler,
+        cache::cache_controller::stats_handler,
+        job::job_controller::create_job_handler,
+        job::job_controller::get_job_handler,
+        watch::watch_controller::create_watch_handler,
+        watch::watch_controller::list_watches_handler,
+        watch::watch_controller::delete_watch_handler,
+        watch::watch_controller::history_handler,
+        alert::alert_controller::create_alert_handler,
+        alert::alert_controller::list_alerts_handler,
+        alert::alert_controller::delete_alert_handler
     ),
     components(
-        schemas(Product, BlibliProduct, crate::tokopedia::tokopedia_dto::TokopediaQuery, crate::blibli::blibli_dto::BlibliQuery)
+        schemas(
+            Product,
+            crate::marketplace::marketplace_dto::SearchQuery,
+            crate::marketplace::marketplace_dto::AggregatedSearchQuery,
+            crate::marketplace::marketplace_dto::AggregatedSearchResult,
+            crate::marketplace::marketplace_dto::SourceError,
+            crate::marketplace::marketplace_model::SortOrder,
+            crate::marketplace::marketplace_model::ProductCondition,
+            crate::marketplace::marketplace_model::FetchMode,
+            crate::marketplace::marketplace_model::ProductDetail,
+            crate::marketplace::marketplace_model::ProductVariant,
+            crate::marketplace::marketplace_dto::ProductDetailQuery,
+            crate::cache::CacheKeyInfo,
+            crate::cache::CacheGroupStats,
+            crate::cache::cache_dto::CacheKeysResult,
+            crate::cache::cache_dto::CacheInvalidateResult,
+            crate::cache::cache_dto::CacheStatsResult,
+            crate::job::job_dto::CreateJobRequest,
+            crate::job::job_dto::CreateJobResult,
+            crate::job::job_model::Job,
+            crate::job::job_model::JobStatus,
+            crate::job::job_model::JobProgress,
+            crate::job::job_model::SourceProgress,
+            crate::job::job_model::CallbackOutcome,
+            crate::watch::watch_dto::CreateWatchRequest,
+            crate::watch::watch_dto::HistoryQuery,
+            crate::watch::watch_dto::HistoryResult,
+            crate::watch::watch_dto::PricePoint,
+            crate::watch::watch_dto::PriceTrend,
+            crate::watch::watch_model::Watch,
+            crate::watch::watch_model::WatchTarget,
+            crate::alert::alert_dto::CreateAlertRequest,
+            crate::alert::alert_model::AlertRule,
+            crate::alert::alert_model::AlertScope,
+            crate::alert::alert_model::AlertCondition,
+            crate::alert::alert_model::AlertEvent
+        )
     ),
     tags(
-        (name = "tokopedia", description = "Tokopedia product scraper API"),
-        (name = "blibli", description = "Blibli product scraper API")
+        (name = "scraper", description = "Marketplace product scraper API (Tokopedia, Blibli)"),
+        (name = "cache", description = "Inspect and invalidate cached results"),
+        (name = "jobs", description = "Scrape in the background and poll for the results"),
+        (name = "watches", description = "Scrape searches and products on a schedule and query their price history, with alerts on price drops")
     ),
     info(
         title = "E-commerce Scraper API",
         version = "0.1.0",
-        description = "API for scraping product data from Tokopedia and Shopee"
+        description = "API for scraping product data from Tokopedia and Blibli"
     )
 )]
 struct ApiDoc;
 
 #[tokio::main]
 async fn main() {
+    // Defaults < config file < legacy environment variables < SCRAPER_* environment variables < command-line flags
+    let cli = Cli::parse();
+    let (settings, layers, warnings) = match Settings::load(&cli) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            // Logging is configured by these settings, report straight to stderr
+            eprintln!("{e:#}");
+            std::process::exit(1);
+        }
+    };
+    if cli.check_config {
+        for warning in &warnings {
+            eprintln!("{warning}");
+        }
+        println!("# Effective configuration ({})\n{}", layers.join(" < "), settings.to_toml());
+        return;
+    }
+    logging::init(&settings.logging);
+    for warning in &warnings {
+        warn!("{warning}");
+    }
+    info!("Effective configuration ({}):\n{}", layers.join(" < "), settings.to_toml());
+    config::init(settings);
+    let settings = config::settings();
+
     // Setup CORS
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    // Build router with all routes from modules
+    // Pages are rendered by Chrome, or downloaded over plain HTTP when a request allows it.
+    // Stored fixtures can stand in for both, e.g. on machines without Chrome
+    let page_sources = match settings.page_source.kind {
+        PageSourceKind::Fixture => {
+            let dir = &settings.page_source.fixture_dir;
+            info!("Serving pages from fixtures in {dir}");
+            let fixtures: Arc<dyn PageSource> = Arc::new(FixturePageSource::new(dir));
+            PageSources { browser: fixtures.clone(), http: fixtures }
+        }
+        PageSourceKind::Chrome => {
+            // Browsers are shared by all requests instead of being launched per call
+            let proxies = Arc::new(ProxyPool::new(&settings.proxy).expect("Invalid proxy settings"));
+            let fingerprints = Arc::new(Fingerprints::new(&settings.fingerprint).expect("Invalid fingerprint settings"));
+            let browser_pool = Arc::new(BrowserPool::new(
+                settings.browser.pool_size,
+                settings.browser.max_open_tabs,
+                Arc::clone(&proxies),
+            ));
+            info!(
+                "Browser pool: {} browser(s), {} concurrent tab(s)",
+                browser_pool.size(),
+                browser_pool.available_tabs()
+            );
+            PageSources {
+                browser: Arc::new(ChromePageSource::new(browser_pool, Arc::clone(&fingerprints))),
+                http: Arc::new(HttpPageSource::new(proxies, fingerprints).expect("Failed to create HTTP page source")),
+            }
+        }
+    };
+    info!(
+        "Page sources: {} (browser), {} (http)",
+        page_sources.browser.name(),
+        page_sources.http.name()
+    );
+
+    // Every registered marketplace is served by /api/scraper/{source}
+    let mut marketplaces = MarketplaceRegistry::default();
+    marketplaces.register(Arc::new(TokopediaMarketplace));
+    marketplaces.register(Arc::new(BlibliMarketplace));
+
+    // One Redis connection shared by the cache and the job store
+    let redis = match redis_client::connect(&settings.redis.url).await {
+        Ok(connection) => Some(connection),
+        Err(e) => {
+            warn!("Redis unavailable: {e:#}");
+            None
+        }
+    };
+
+    // Shared by every request; Redis when available, in-process otherwise
+    let cache = match cache::connect(&settings.cache, redis.clone()) {
+        Ok(cache) => cache,
+        Err(e) => {
+            error!("{e:#}");
+            std::process::exit(1);
+        }
+    };
+    info!("Cache backend: {}", cache.name());
+
+    // Jobs survive restarts when they are kept in Redis
+    let job_store = job::store(redis);
+    info!("Job store: {}", job_store.name());
+
+    // Watchlists and the price history they record
+    let watch_store = match watch::open(&settings.watch).await {
+        Ok(store) => store,
+        Err(e) => {
+            error!("Failed to open watch store: {e:#}");
+            std::process::exit(1);
+        }
+    };
+    info!("Watch store: {}", watch_store.name());
+
+    let state = AppState::new(page_sources, marketplaces, cache)
+        .with_job_store(job_store)
+        .with_watch_store(watch_store);
+    match state.jobs.resume(&state).await {
+        Ok(0) => {}
+        Ok(count) => info!("Resumed {count} interrupted job(s)"),
+        Err(e) => warn!("Failed to resume interrupted jobs: {e}"),
+    }
+
+    // Popular searches are scraped before anyone waits for them
+    if let Err(e) = cache::cache_prewarm::spawn(state.clone()) {
+        error!("Invalid prewarm configuration: {e:#}");
+        std::process::exit(1);
+    }
+    watch::watch_scheduler::spawn(state.clone());
+
+    // Build router with all routes from modules. The cache admin endpoints are
+    // merged after CORS, so web pages on other origins cannot call them
     let app = Router::new()
-        .merge(tokopedia::router())
-        .merge(blibli::router())
+        .merge(marketplace::router())
+        .merge(job::router())
+        .merge(watch::router())
+        .merge(alert::router())
+        .merge(metrics::router())
         .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .layer(cors)
+        .merge(cache::router())
         .layer(middleware::from_fn(logging_middleware))
-        .layer(cors);
+        .with_state(state);
 
     // Start server
-    let addr = format!("{SERVER_HOST}:{SERVER_PORT}");
+    let addr = format!("{}:{}", settings.server.host, settings.server.port);
     let listener = tokio::net::TcpListener::bind(&addr)
         .await
         .unwrap_or_else(|_| panic!("Failed to bind to {addr}"));
 
-    println!("🚀 E-commerce Scraper API running on http://{addr}");
-    println!("📡 Tokopedia: GET http://{addr}/api/scraper/tokopedia?query=iphone&limit=10");
-    println!("📡 Blibli: GET http://{addr}/api/scraper/blibli?query=iphone&limit=10");
-    println!("📚 Swagger UI: http://{addr}/docs");
+    info!("E-commerce Scraper API running on http://{addr}");
+    info!("Tokopedia: GET http://{addr}/api/scraper/tokopedia?query=iphone&limit=10");
+    info!("Blibli: GET http://{addr}/api/scraper/blibli?query=iphone&limit=10");
+    info!("All sources: GET http://{addr}/api/scraper/search?sources=tokopedia,blibli&query=iphone&limit=10");
+    info!("Product detail: GET http://{addr}/api/scraper/product?url=https://www.tokopedia.com/...");
+    info!("Jobs: POST http://{addr}/api/scraper/jobs, GET http://{addr}/api/scraper/jobs/{{id}}");
+    info!("Watches: POST http://{addr}/api/scraper/watches, GET http://{addr}/api/scraper/history?product=https://www.tokopedia.com/...");
+    info!("Price alerts: POST http://{addr}/api/scraper/alerts");
+    info!("Cache admin: GET http://{addr}/api/scraper/cache/stats, GET .../cache/keys, DELETE .../cache?marketplace=tokopedia");
+    if settings.cache.admin_token.is_empty() {
+        warn!("Cache admin endpoints are disabled and answer 401, set cache.admin_token to enable them");
+    }
+    info!("Metrics: GET http://{addr}/metrics");
+    info!("Swagger UI: http://{addr}/docs");
 
     axum::serve(listener, app)
         .await
@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::cache::CachePolicy;
+use crate::error::{ApiResult, ScraperError};
+use crate::marketplace::marketplace_dto::{
+    AggregatedSearchQuery, AggregatedSearchResult, ApiResponse, ProductDetailQuery, SearchQuery,
+    SearchRequest,
+};
+use crate::marketplace::marketplace_model::{FetchMode, Product, ProductDetail};
+use crate::marketplace::marketplace_service::MarketplaceService;
+use crate::state::AppState;
+
+/// Response schema for successful API responses
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiResponseSuccess {
+    /// Indicates if the request was successful
+    pub success: bool,
+    /// Response data containing the scraped products
+    pub data: Vec<Product>,
+    /// Number of items returned
+    pub count: usize,
+    /// Marketplace result pages the products were collected from
+    pub pages_fetched: Vec<u32>,
+}
+
+/// Response schema for error API responses
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiResponseError {
+    /// Indicates if the request was successful
+    pub success: bool,
+    /// Error message
+    pub error: String,
+    /// Machine-readable error code: browser_launch, navigation_timeout, blocked, no_results,
+    /// parse_failure, cache_unavailable, invalid_request, unauthorized, not_found
+    /// or internal
+    pub error_code: String,
+    /// Number of items returned (always 0 for errors)
+    pub count: usize,
+}
+
+/// HTTP handler for searching any registered marketplace
+#[utoipa::path(
+    get,
+    path = "/api/scraper/{source}",
+    params(
+        ("source" = String, Path, description = "Marketplace identifier, e.g. `tokopedia` or `blibli`"),
+        SearchQuery
+    ),
+    responses(
+        (status = 200, description = "Successfully scraped marketplace products", body = inline(ApiResponseSuccess)),
+        (status = 400, description = "Invalid limit or filters, or mode=http on a marketplace that needs a browser", body = inline(ApiResponseError)),
+        (status = 404, description = "Unknown marketplace", body = inline(ApiResponseError)),
+        (status = 422, description = "The search found no products (`no_results`)", body = inline(ApiResponseError)),
+        (status = 429, description = "The marketplace blocked the scraper", body = inline(ApiResponseError)),
+        (status = 500, description = "Internal error", body = inline(ApiResponseError)),
+        (status = 502, description = "The results page could not be parsed", body = inline(ApiResponseError)),
+        (status = 503, description = "Browser could not be launched", body = inline(ApiResponseError)),
+        (status = 504, description = "The marketplace page did not load in time", body = inline(ApiResponseError))
+    ),
+    tag = "scraper"
+)]
+pub async fn search_handler(
+    State(state): State<AppState>,
+    Path(source): Path<String>,
+    Query(params): Query<SearchQuery>,
+) -> ApiResult<Vec<Product>> {
+    let request = SearchRequest::from(params);
+
+    info!(
+        "Received {source} request: query='{}', limit={}, page={}, pages={}, mode={}",
+        request.query,
+        request.limit,
+        request.page,
+        request.max_pages,
+        request.mode.as_str()
+    );
+
+    request.validate().map_err(|e| ScraperError::InvalidRequest(e.to_string()))?;
+
+    let Some(marketplace) = state.marketplaces.get(&source) else {
+        return Err(ScraperError::NotFound(format!(
+            "Unknown marketplace '{source}'. Available: {}",
+            state.marketplaces.ids().join(", ")
+        )));
+    };
+
+    if request.mode == FetchMode::Http && !marketplace.supports_http_mode() {
+        return Err(ScraperError::InvalidRequest(format!(
+            "{} does not support mode=http, its results need a browser",
+            marketplace.display_name()
+        )));
+    }
+
+    let display_name = marketplace.display_name();
+    let service = MarketplaceService::new(marketplace, &state);
+    let results = service.search_products(&request).await?;
+    if results.products.is_empty() {
+        return Err(ScraperError::NoResults(format!("{display_name} has no products for '{}'", request.query)));
+    }
+    let count = results.products.len();
+    let response = ApiResponse::success(results.products, count).with_pages_fetched(results.pages_fetched);
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// HTTP handler that searches several marketplaces concurrently
+#[utoipa::path(
+    get,
+    path = "/api/scraper/search",
+    params(AggregatedSearchQuery),
+    responses(
+        (status = 200, description = "Merged products from every marketplace that succeeded, plus per-source errors", body = ApiResponse<AggregatedSearchResult>),
+        (status = 400, description = "Unknown marketplace in `sources`, invalid limit or filters", body = inline(ApiResponseError)),
+        (status = 422, description = "No marketplace found any product (`no_results`)", body = ApiResponse<AggregatedSearchResult>),
+        (status = 429, description = "Every marketplace failed, most of them blocked the scraper; `data.errors` lists each failure", body = ApiResponse<AggregatedSearchResult>),
+        (status = 500, description = "Every marketplace failed, most of them with an internal error", body = ApiResponse<AggregatedSearchResult>),
+        (status = 502, description = "Every marketplace failed, most of their results could not be parsed", body = ApiResponse<AggregatedSearchResult>),
+        (status = 503, description = "Every marketplace failed, most of them because Chrome could not be launched", body = ApiResponse<AggregatedSearchResult>),
+        (status = 504, description = "Every marketplace failed, most of them timed out", body = ApiResponse<AggregatedSearchResult>)
+    ),
+    tag = "scraper"
+)]
+pub async fn aggregated_search_handler(
+    State(state): State<AppState>,
+    Query(params): Query<AggregatedSearchQuery>,
+) -> ApiResult<AggregatedSearchResult> {
+    let marketplaces = state
+        .marketplaces
+        .resolve(params.sources.as_deref())
+        .map_err(|e| ScraperError::InvalidRequest(e.to_string()))?;
+
+    let request = SearchRequest::from(params);
+    let sources: Vec<_> = marketplaces.iter().map(|m| m.id()).collect();
+    info!(
+        "Received aggregated request: sources={sources:?}, query='{}', limit={}, page={}, pages={}, mode={}",
+        request.query,
+        request.limit,
+        request.page,
+        request.max_pages,
+        request.mode.as_str()
+    );
+
+    request.validate().map_err(|e| ScraperError::InvalidRequest(e.to_string()))?;
+
+    let result = MarketplaceService::search_all(marketplaces, &state, &request, None).await;
+
+    let count = result.products.len();
+    let failure = result.failure().or_else(|| {
+        (count == 0).then(|| ScraperError::NoResults(format!("No marketplace has products for '{}'", request.query)))
+    });
+    let response = ApiResponse::success(result, count);
+    match failure {
+        // The per-source errors stay in `data`
+        Some(error) => Ok((error.status(), Json(response.with_error(&error)))),
+        None => Ok((StatusCode::OK, Json(response))),
+    }
+}
+
+/// HTTP handler for scraping the details of a single product page
+#[utoipa::path(
+    get,
+    path = "/api/scraper/product",
+    params(ProductDetailQuery),
+    responses(
+        (status = 200, description = "Successfully scraped product details", body = ApiResponse<ProductDetail>),
+        (status = 400, description = "URL is invalid or not on a supported marketplace", body = inline(ApiResponseError)),
+        (status = 422, description = "No product found on the page (`no_results`)", body = inline(ApiResponseError)),
+        (status = 429, description = "The marketplace blocked the scraper", body = inline(ApiResponseError)),
+        (status = 500, description = "Internal error", body = inline(ApiResponseError)),
+        (status = 502, description = "The product page is the marketplace's error page", body = inline(ApiResponseError)),
+        (status = 503, description = "Browser could not be launched", body = inline(ApiResponseError)),
+        (status = 504, description = "The product page did not load in time", body = inline(ApiResponseError))
+    ),
+    tag = "scraper"
+)]
+pub async fn product_detail_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ProductDetailQuery>,
+) -> ApiResult<ProductDetail> {
+    info!("Received product detail request: url='{}'", params.url);
+
+    let marketplace = state
+        .marketplaces
+        .find_by_url(&params.url)
+        .map_err(|e| ScraperError::InvalidRequest(e.to_string()))?;
+
+    let service = MarketplaceService::new(marketplace, &state);
+    match service.product_detail(&params.url, &CachePolicy::new(params.fresh, params.max_age)).await? {
+        Some(detail) => Ok((StatusCode::OK, Json(ApiResponse::success(detail, 1)))),
+        None => Err(ScraperError::NoResults(format!("No product found at {}", params.url))),
+    }
+}
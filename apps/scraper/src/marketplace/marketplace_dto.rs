@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{ToSchema, IntoParams};
+
+use crate::cache::CachePolicy;
+use crate::config::settings;
+use crate::error::ScraperError;
+
+use crate::marketplace::marketplace_model::{
+    FetchMode, Product, ProductCondition, SearchFilters, SortOrder,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct SearchQuery {
+    /// Search query for products (default: "iphone")
+    #[serde(default = "crate::config::default_query")]
+    pub query: String,
+    /// Maximum number of products to return (default: 10, max: 1000)
+    #[serde(default = "crate::config::default_limit")]
+    pub limit: usize,
+    /// First results page to fetch (default: 1)
+    pub page: Option<u32>,
+    /// Maximum number of result pages to follow when `limit` needs more than one page (default: 5, max: 20)
+    pub pages: Option<u32>,
+    /// Minimum price in rupiah
+    pub min_price: Option<u64>,
+    /// Maximum price in rupiah
+    pub max_price: Option<u64>,
+    /// Sort order: relevance, lowest_price, highest_price, newest or best_selling (default: relevance)
+    pub sort: Option<SortOrder>,
+    /// Only list products from official stores (default: false)
+    #[serde(default)]
+    pub official_store_only: bool,
+    /// Item condition: new or used
+    pub condition: Option<ProductCondition>,
+    /// Minimum product rating out of 5
+    pub rating_min: Option<f32>,
+    /// How pages are fetched: http, browser or auto (default: auto)
+    pub mode: Option<FetchMode>,
+    /// Skip cached results and overwrite them with a new scrape (default: false)
+    #[serde(default)]
+    pub fresh: bool,
+    /// Only accept cached results stored at most this many seconds ago
+    pub max_age: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct AggregatedSearchQuery {
+    /// Comma-separated marketplace ids, e.g. "tokopedia,blibli" (default: all registered)
+    pub sources: Option<String>,
+    /// Search query for products (default: "iphone")
+    #[serde(default = "crate::config::default_query")]
+    pub query: String,
+    /// Maximum number of products to return per marketplace (default: 10, max: 1000)
+    #[serde(default = "crate::config::default_limit")]
+    pub limit: usize,
+    /// First results page to fetch (default: 1)
+    pub page: Option<u32>,
+    /// Maximum number of result pages to follow per marketplace (default: 5, max: 20)
+    pub pages: Option<u32>,
+    /// Minimum price in rupiah
+    pub min_price: Option<u64>,
+    /// Maximum price in rupiah
+    pub max_price: Option<u64>,
+    /// Sort order: relevance, lowest_price, highest_price, newest or best_selling (default: relevance)
+    pub sort: Option<SortOrder>,
+    /// Only list products from official stores (default: false)
+    #[serde(default)]
+    pub official_store_only: bool,
+    /// Item condition: new or used
+    pub condition: Option<ProductCondition>,
+    /// Minimum product rating out of 5
+    pub rating_min: Option<f32>,
+    /// How pages are fetched: http, browser or auto (default: auto)
+    pub mode: Option<FetchMode>,
+    /// Skip cached results and overwrite them with a new scrape (default: false)
+    #[serde(default)]
+    pub fresh: bool,
+    /// Only accept cached results stored at most this many seconds ago
+    pub max_age: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct ProductDetailQuery {
+    /// Product page URL on a supported marketplace
+    pub url: String,
+    /// Skip the cached product and overwrite it with a new scrape (default: false)
+    #[serde(default)]
+    pub fresh: bool,
+    /// Only accept a cached product stored at most this many seconds ago
+    pub max_age: Option<u64>,
+}
+
+/// Normalized parameters of a marketplace search
+#[derive(Debug, Clone)]
+pub struct SearchRequest {
+    pub query: String,
+    pub limit: usize,
+    /// First results page to fetch (1-based)
+    pub page: u32,
+    /// Maximum number of pages to follow from `page`
+    pub max_pages: u32,
+    /// Filters and sort order passed on to the marketplace
+    pub filters: SearchFilters,
+    /// How result pages are fetched
+    pub mode: FetchMode,
+    /// How cached results may be used
+    pub cache: CachePolicy,
+}
+
+impl SearchRequest {
+    pub fn new(query: String, limit: usize, page: Option<u32>, pages: Option<u32>, filters: SearchFilters) -> Self {
+        let search = &settings().search;
+        Self {
+            query,
+            limit,
+            page: page.unwrap_or(1).max(1),
+            max_pages: pages.unwrap_or(search.default_max_pages).clamp(1, search.max_pages_limit),
+            filters,
+            mode: FetchMode::default(),
+            cache: CachePolicy::default(),
+        }
+    }
+
+    /// Reject a `limit` outside `1..=search.max_limit` and filters no marketplace can answer
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let max_limit = settings().search.max_limit;
+        if !(1..=max_limit).contains(&self.limit) {
+            anyhow::bail!("limit must be between 1 and {max_limit}, got {}", self.limit);
+        }
+        self.filters.validate()
+    }
+
+    pub fn with_mode(mut self, mode: Option<FetchMode>) -> Self {
+        self.mode = mode.unwrap_or_default();
+        self
+    }
+
+    pub fn with_cache_policy(mut self, cache: CachePolicy) -> Self {
+        self.cache = cache;
+        self
+    }
+}
+
+impl From<SearchQuery> for SearchRequest {
+    fn from(params: SearchQuery) -> Self {
+        let filters = SearchFilters {
+            min_price: params.min_price,
+            max_price: params.max_price,
+            sort: params.sort.unwrap_or_default(),
+            official_store_only: params.official_store_only,
+            condition: params.condition,
+            rating_min: params.rating_min,
+        };
+        Self::new(params.query, params.limit, params.page, params.pages, filters)
+            .with_mode(params.mode)
+            .with_cache_policy(CachePolicy::new(params.fresh, params.max_age))
+    }
+}
+
+/// The same search on each marketplace, without `sources`
+///
+/// Both structs are destructured and built in full, so a parameter added to
+/// one of them does not compile until it is added to the other.
+impl From<AggregatedSearchQuery> for SearchQuery {
+    fn from(params: AggregatedSearchQuery) -> Self {
+        let AggregatedSearchQuery {
+            sources: _,
+            query,
+            limit,
+            page,
+            pages,
+            min_price,
+            max_price,
+            sort,
+            official_store_only,
+            condition,
+            rating_min,
+            mode,
+            fresh,
+            max_age,
+        } = params;
+        Self {
+            query,
+            limit,
+            page,
+            pages,
+            min_price,
+            max_price,
+            sort,
+            official_store_only,
+            condition,
+            rating_min,
+            mode,
+            fresh,
+            max_age,
+        }
+    }
+}
+
+impl From<AggregatedSearchQuery> for SearchRequest {
+    fn from(params: AggregatedSearchQuery) -> Self {
+        SearchQuery::from(params).into()
+    }
+}
+
+/// Failure of a single marketplace in an aggregated search
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SourceError {
+    /// Marketplace that failed
+    pub source: String,
+    /// Machine-readable error code, as in the response envelope
+    pub error_code: String,
+    /// Error message
+    pub error: String,
+}
+
+/// Merged results of an aggregated search
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AggregatedSearchResult {
+    /// Products from every marketplace that succeeded, tagged with their source
+    pub products: Vec<Product>,
+    /// One entry per marketplace that failed
+    pub errors: Vec<SourceError>,
+    /// Result pages fetched from each marketplace that succeeded
+    pub pages_fetched: BTreeMap<String, Vec<u32>>,
+}
+
+impl AggregatedSearchResult {
+    /// The error of the whole search, `None` unless every marketplace failed
+    ///
+    /// Its kind is the one most marketplaces failed with, the first one's on a tie.
+    pub fn failure(&self) -> Option<ScraperError> {
+        if !self.pages_fetched.is_empty() {
+            return None;
+        }
+        let count = |code: &str| self.errors.iter().filter(|e| e.error_code == code).count();
+        let most_common = self.errors.iter().rev().max_by_key(|e| count(&e.error_code))?;
+        let message = self.errors.iter().map(|e| format!("{}: {}", e.source, e.error)).collect::<Vec<_>>().join("; ");
+        Some(ScraperError::from_code(&most_common.error_code, message))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApiResponse<T> {
+    /// Indicates if the request was successful
+    pub success: bool,
+    /// Response data (present only on success)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    /// Error message (present only on failure)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Machine-readable error code (present only on failure), see [`ScraperError::code`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Number of items returned
+    pub count: usize,
+    /// Marketplace result pages the data was collected from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_fetched: Option<Vec<u32>>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Successful response, its `count` is logged with the request
+    pub fn success(data: T, count: usize) -> Self {
+        tracing::Span::current().record("count", count);
+        Self {
+            success: true,
+            data: Some(data),
+            error: None,
+            error_code: None,
+            count,
+            pages_fetched: None,
+        }
+    }
+
+    pub fn failure(error: &ScraperError) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(error.to_string()),
+            error_code: Some(error.code().to_string()),
+            count: 0,
+            pages_fetched: None,
+        }
+    }
+
+    /// Mark the response as failed with `error`, keeping its data
+    pub fn with_error(mut self, error: &ScraperError) -> Self {
+        self.success = false;
+        self.error = Some(error.to_string());
+        self.error_code = Some(error.code().to_string());
+        self
+    }
+
+    pub fn with_pages_fetched(mut self, pages: Vec<u32>) -> Self {
+        self.pages_fetched = Some(pages);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(failures: &[(&str, &str)], succeeded: &[&str]) -> AggregatedSearchResult {
+        AggregatedSearchResult {
+            products: Vec::new(),
+            errors: failures
+                .iter()
+                .map(|(source, code)| SourceError { source: source.to_string(), error_code: code.to_string(), error: "failed".to_string() })
+                .collect(),
+            pages_fetched: succeeded.iter().map(|source| (source.to_string(), vec![1])).collect(),
+        }
+    }
+
+    #[test]
+    fn test_aggregated_query_becomes_the_same_request() {
+        let params = serde_json::json!({
+            "query": "laptop", "limit": 20, "pages": 2, "min_price": 5000000, "sort": "lowest_price",
+            "official_store_only": true, "rating_min": 4.5, "mode": "http", "max_age": 600
+        });
+        let single = SearchRequest::from(serde_json::from_value::<SearchQuery>(params.clone()).unwrap());
+        let mut params = params;
+        params["sources"] = "tokopedia,blibli".into();
+        let aggregated = SearchRequest::from(serde_json::from_value::<AggregatedSearchQuery>(params).unwrap());
+
+        assert_eq!(aggregated.filters, single.filters);
+        assert_eq!(aggregated.cache, single.cache);
+        assert_eq!(aggregated.mode, single.mode);
+        assert_eq!((aggregated.query, aggregated.limit, aggregated.max_pages), (single.query, single.limit, single.max_pages));
+    }
+
+    #[test]
+    fn test_limit_must_be_within_bounds() {
+        let request = |limit| SearchRequest::new("laptop".to_string(), limit, None, None, SearchFilters::default());
+        assert!(request(1).validate().is_ok());
+        assert!(request(settings().search.max_limit).validate().is_ok());
+        assert!(request(0).validate().unwrap_err().to_string().contains("limit"));
+        assert!(request(settings().search.max_limit + 1).validate().is_err());
+    }
+
+    #[test]
+    fn test_search_fails_only_when_every_source_failed() {
+        assert_eq!(result(&[("blibli", "blocked")], &["tokopedia"]).failure(), None);
+
+        let error = result(&[("tokopedia", "blocked"), ("blibli", "blocked")], &[]).failure().unwrap();
+        assert_eq!(error, ScraperError::Blocked("tokopedia: failed; blibli: failed".to_string()));
+
+        let error = result(&[("tokopedia", "navigation_timeout"), ("blibli", "blocked")], &[]).failure().unwrap();
+        assert_eq!(error.code(), "navigation_timeout");
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Product listing scraped from any marketplace search page
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Product {
+    /// Product name
+    pub name: String,
+    /// Product price as display string, e.g. "Rp60.270"
+    pub price: String,
+    /// Current selling price in rupiah
+    #[serde(default)]
+    pub price_idr: u64,
+    /// Price before discount in rupiah (present only for discounted listings)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub original_price_idr: Option<u64>,
+    /// Discount percentage (present only for discounted listings)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discount_percent: Option<u8>,
+    /// Product rating out of 5 (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f32>,
+    /// Number of reviews behind the rating (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub review_count: Option<u64>,
+    /// Product image URL
+    pub image_url: String,
+    /// Product page URL
+    pub product_url: String,
+    /// Shop location (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shop_location: Option<String>,
+    /// Number of items sold as displayed, e.g. "10rb+ terjual" (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold: Option<String>,
+    /// Lower bound of the number of items sold, parsed from `sold` (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sold_min: Option<u64>,
+    /// Marketplace the product was scraped from, e.g. `tokopedia`
+    #[serde(default)]
+    pub source: String,
+}
+
+/// One purchasable variant of a product, e.g. a color or size
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, ToSchema)]
+pub struct ProductVariant {
+    /// Variant name, e.g. "Hitam, 128GB"
+    pub name: String,
+    /// Variant price in rupiah, when the page lists it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub price_idr: Option<u64>,
+    /// Units in stock, when the page lists it
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stock: Option<u64>,
+    /// Whether the variant can currently be bought
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub available: Option<bool>,
+}
+
+/// Full details of a single product page
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct ProductDetail {
+    /// Product name
+    pub name: String,
+    /// Product price as display string, e.g. "Rp60.270"
+    pub price: String,
+    /// Current selling price in rupiah (lowest variant price for products with variants)
+    pub price_idr: u64,
+    /// Price before discount in rupiah (present only for discounted listings)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub original_price_idr: Option<u64>,
+    /// Discount percentage (present only for discounted listings)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discount_percent: Option<u8>,
+    /// Full product description as plain text
+    pub description: String,
+    /// Every product image URL, main image first
+    pub images: Vec<String>,
+    /// Purchasable variants, empty for products without variants
+    pub variants: Vec<ProductVariant>,
+    /// Total units in stock (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stock: Option<u64>,
+    /// Shipping weight in grams (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub weight_grams: Option<u64>,
+    /// Name of the shop selling the product (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub shop_name: Option<String>,
+    /// Shop badge, e.g. "Official Store" or "Power Merchant" (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub shop_badge: Option<String>,
+    /// Category breadcrumbs from the top-level category down
+    pub categories: Vec<String>,
+    /// Product rating out of 5 (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rating: Option<f32>,
+    /// Number of reviews behind the rating (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub review_count: Option<u64>,
+    /// Lower bound of the number of items sold (optional)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sold_min: Option<u64>,
+    /// Product page URL
+    pub product_url: String,
+    /// Marketplace the product was scraped from, e.g. `tokopedia`
+    pub source: String,
+}
+
+/// Products collected from one or more result pages of a search
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SearchResults {
+    /// Products in page order, without duplicate product URLs
+    pub products: Vec<Product>,
+    /// Result pages that were fetched
+    pub pages_fetched: Vec<u32>,
+    /// Whether the marketplace ran out of results before the page budget was used
+    pub exhausted: bool,
+}
+
+/// Sort order of marketplace search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Relevance,
+    LowestPrice,
+    HighestPrice,
+    Newest,
+    BestSelling,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Relevance => "relevance",
+            SortOrder::LowestPrice => "lowest_price",
+            SortOrder::HighestPrice => "highest_price",
+            SortOrder::Newest => "newest",
+            SortOrder::BestSelling => "best_selling",
+        }
+    }
+}
+
+/// Item condition filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductCondition {
+    New,
+    Used,
+}
+
+impl ProductCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductCondition::New => "new",
+            ProductCondition::Used => "used",
+        }
+    }
+}
+
+/// How search pages are fetched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchMode {
+    /// Plain HTTP only, reading the results embedded in the page
+    Http,
+    /// Always render the page in headless Chrome
+    Browser,
+    /// Plain HTTP first, Chrome when the embedded results are missing or blocked
+    #[default]
+    Auto,
+}
+
+impl FetchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FetchMode::Http => "http",
+            FetchMode::Browser => "browser",
+            FetchMode::Auto => "auto",
+        }
+    }
+}
+
+/// Filters and sort order applied by the marketplace itself
+///
+/// Each marketplace translates these into its own search URL parameters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilters {
+    /// Minimum price in rupiah
+    pub min_price: Option<u64>,
+    /// Maximum price in rupiah
+    pub max_price: Option<u64>,
+    pub sort: SortOrder,
+    /// Only list products from official stores
+    pub official_store_only: bool,
+    pub condition: Option<ProductCondition>,
+    /// Minimum product rating out of 5
+    pub rating_min: Option<f32>,
+}
+
+impl SearchFilters {
+    /// Reject filter combinations no marketplace can answer
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let (Some(min), Some(max)) = (self.min_price, self.max_price) {
+            if min > max {
+                anyhow::bail!("min_price ({min}) must not be greater than max_price ({max})");
+            }
+        }
+        if let Some(rating) = self.rating_min {
+            if !(0.0..=5.0).contains(&rating) {
+                anyhow::bail!("rating_min must be between 0 and 5, got {rating}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a parsed product passes the filters marketplaces only apply in part
+    ///
+    /// Marketplaces filter ratings on whole stars, so a fractional `rating_min`
+    /// is checked again here; products without a rating do not pass it.
+    pub fn accepts(&self, product: &Product) -> bool {
+        match self.rating_min {
+            Some(min) if min.fract() != 0.0 => product.rating.is_some_and(|rating| rating >= min),
+            _ => true,
+        }
+    }
+
+    /// Stable cache key fragment, empty when no filter is set
+    pub fn cache_key_suffix(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(min) = self.min_price {
+            parts.push(format!("min_price={min}"));
+        }
+        if let Some(max) = self.max_price {
+            parts.push(format!("max_price={max}"));
+        }
+        if self.sort != SortOrder::Relevance {
+            parts.push(format!("sort={}", self.sort.as_str()));
+        }
+        if self.official_store_only {
+            parts.push("official".to_string());
+        }
+        if let Some(condition) = self.condition {
+            parts.push(format!("condition={}", condition.as_str()));
+        }
+        if let Some(rating) = self.rating_min {
+            parts.push(format!("rating_min={rating}"));
+        }
+        parts.iter().map(|part| format!(":{part}")).collect()
+    }
+}
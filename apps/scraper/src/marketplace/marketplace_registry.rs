@@ -0,0 +1,117 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use url::Url;
+
+use crate::marketplace::Marketplace;
+
+/// Marketplaces available to the scraper, keyed by their identifier
+#[derive(Default)]
+pub struct MarketplaceRegistry {
+    marketplaces: BTreeMap<&'static str, Arc<dyn Marketplace>>,
+}
+
+impl MarketplaceRegistry {
+    /// Register a marketplace, replacing any previous one with the same identifier
+    pub fn register(&mut self, marketplace: Arc<dyn Marketplace>) {
+        self.marketplaces.insert(marketplace.id(), marketplace);
+    }
+
+    /// Look up a marketplace by identifier (case-insensitive)
+    pub fn get(&self, id: &str) -> Option<Arc<dyn Marketplace>> {
+        self.marketplaces.get(id.to_lowercase().as_str()).cloned()
+    }
+
+    /// Identifiers of all registered marketplaces, sorted
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.marketplaces.keys().copied().collect()
+    }
+
+    /// Resolve a comma-separated list of identifiers, or every marketplace when `None`
+    ///
+    /// Duplicates are ignored and the requested order is kept.
+    pub fn resolve(&self, ids: Option<&str>) -> Result<Vec<Arc<dyn Marketplace>>> {
+        let requested: Vec<&str> = ids
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .collect();
+        if requested.is_empty() {
+            return Ok(self.marketplaces.values().cloned().collect());
+        }
+
+        let mut resolved: Vec<Arc<dyn Marketplace>> = Vec::new();
+        for id in requested {
+            let Some(marketplace) = self.get(id) else {
+                bail!("Unknown marketplace '{id}'. Available: {}", self.ids().join(", "));
+            };
+            if !resolved.iter().any(|m| m.id() == marketplace.id()) {
+                resolved.push(marketplace);
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Find the marketplace a product page URL belongs to
+    pub fn find_by_url(&self, url: &str) -> Result<Arc<dyn Marketplace>> {
+        let parsed = Url::parse(url).map_err(|e| anyhow!("Invalid product URL '{url}': {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            bail!("Invalid product URL '{url}': only http and https are supported");
+        }
+        self.marketplaces
+            .values()
+            .find(|marketplace| marketplace.owns_url(&parsed))
+            .cloned()
+            .ok_or_else(|| anyhow!("URL '{url}' does not belong to a supported marketplace ({})", self.ids().join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blibli::BlibliMarketplace;
+    use crate::tokopedia::TokopediaMarketplace;
+
+    fn registry() -> MarketplaceRegistry {
+        let mut registry = MarketplaceRegistry::default();
+        registry.register(Arc::new(TokopediaMarketplace));
+        registry.register(Arc::new(BlibliMarketplace));
+        registry
+    }
+
+    fn ids(marketplaces: &[Arc<dyn Marketplace>]) -> Vec<&'static str> {
+        marketplaces.iter().map(|m| m.id()).collect()
+    }
+
+    #[test]
+    fn test_resolve_defaults_to_all_marketplaces() {
+        let resolved = registry().resolve(None).unwrap();
+        assert_eq!(ids(&resolved), vec!["blibli", "tokopedia"]);
+    }
+
+    #[test]
+    fn test_resolve_keeps_order_and_drops_duplicates() {
+        let resolved = registry().resolve(Some(" Tokopedia, blibli,tokopedia ")).unwrap();
+        assert_eq!(ids(&resolved), vec!["tokopedia", "blibli"]);
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_marketplace() {
+        let error = registry().resolve(Some("tokopedia,shopee")).err().unwrap();
+        assert!(error.to_string().contains("shopee"));
+    }
+
+    #[test]
+    fn test_find_by_url() {
+        let registry = registry();
+        let found = registry.find_by_url("https://www.tokopedia.com/shop/kaos-polos-123").unwrap();
+        assert_eq!(found.id(), "tokopedia");
+        let found = registry.find_by_url("https://blibli.com/p/kaos-polos/ps--ABC-123").unwrap();
+        assert_eq!(found.id(), "blibli");
+
+        assert!(registry.find_by_url("https://www.nottokopedia.com/item").is_err());
+        assert!(registry.find_by_url("tokopedia.com/shop/item").is_err());
+    }
+}
@@ -0,0 +1,489 @@
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
+
+use crate::cache::{self, Cache, CacheEvent, CachePolicy, CacheStats, CachedEntry, RefreshTracker};
+use crate::config::settings;
+use crate::error::ScraperError;
+use crate::marketplace::marketplace_dto::{AggregatedSearchResult, SearchRequest, SourceError};
+use crate::marketplace::marketplace_model::{Product, ProductDetail, SearchResults};
+use crate::marketplace::Marketplace;
+use crate::page_source::PageSources;
+use crate::state::AppState;
+
+/// Called after every scraped result page with the marketplace id, the page number
+/// and the number of products the marketplace has collected so far
+pub type PageProgress = Arc<dyn Fn(&str, u32, usize) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct MarketplaceService {
+    marketplace: Arc<dyn Marketplace>,
+    page_sources: PageSources,
+    cache: Arc<dyn Cache>,
+    cache_stats: Arc<CacheStats>,
+    refreshes: Arc<RefreshTracker>,
+    on_page: Option<PageProgress>,
+}
+
+impl MarketplaceService {
+    pub fn new(marketplace: Arc<dyn Marketplace>, state: &AppState) -> Self {
+        Self {
+            marketplace,
+            page_sources: state.page_sources.clone(),
+            cache: Arc::clone(&state.cache),
+            cache_stats: Arc::clone(&state.cache_stats),
+            refreshes: Arc::clone(&state.refreshes),
+            on_page: None,
+        }
+    }
+
+    /// Report every scraped result page to `on_page`
+    pub fn with_progress(mut self, on_page: Option<PageProgress>) -> Self {
+        self.on_page = on_page;
+        self
+    }
+
+    /// Main business logic for searching products on the service's marketplace
+    #[instrument(name = "search", skip_all, fields(marketplace = self.marketplace.id(), query = %request.query))]
+    pub async fn search_products(&self, request: &SearchRequest) -> Result<SearchResults> {
+        let start = Instant::now();
+        let name = self.marketplace.display_name();
+        let query = request.query.as_str();
+        let limit = request.limit;
+
+        info!(
+            "Searching for '{query}' on {name} (pages {}..{}, mode {})...",
+            request.page,
+            request.page + request.max_pages - 1,
+            request.mode.as_str()
+        );
+
+        let cache_key = cache::search_key(self.marketplace.id(), query, &request.filters, request.page);
+
+        if let Some(entry) = self.cache_get::<SearchResults>(&cache_key, &request.cache).await {
+            let age = entry.age();
+            let mut results = entry.value;
+            // A smaller cached entry only answers the request if no more pages exist
+            if results.products.len() >= limit || results.exhausted {
+                if age > settings().cache.search_soft_ttl() {
+                    // Answer right away, the next request gets the refreshed entry
+                    self.cache_stats.record(&cache_key, CacheEvent::Stale);
+                    info!("Cached entry for query: {query} is {age:?} old, refreshing it in the background");
+                    let mut refresh = request.clone();
+                    refresh.limit = limit.max(results.products.len());
+                    self.spawn_search_refresh(refresh);
+                } else {
+                    self.cache_stats.record(&cache_key, CacheEvent::Hit);
+                }
+                // Apply limit to cached results
+                results.products.truncate(limit);
+                info!("Cache hit for query: {query}, returning {} products (total: {:?})", results.products.len(), start.elapsed());
+                return Ok(results);
+            }
+            self.cache_stats.record(&cache_key, CacheEvent::Miss);
+            info!("Cached entry has {} products but {limit} requested, scraping more pages", results.products.len());
+        }
+
+        let mut results = self.scrape_and_cache(request, &cache_key).await?;
+        results.products.truncate(limit);
+        Ok(results)
+    }
+
+    /// Scrape a search again and replace its cached entry
+    ///
+    /// Returns `false` without scraping when a refresh of the same search is
+    /// already running.
+    pub async fn refresh_search(&self, request: &SearchRequest) -> Result<bool> {
+        let cache_key = cache::search_key(self.marketplace.id(), &request.query, &request.filters, request.page);
+        let Some(_refresh) = self.refreshes.start(&cache_key) else {
+            debug!("Refresh of {cache_key} already running");
+            return Ok(false);
+        };
+        self.scrape_and_cache(request, &cache_key).await?;
+        self.cache_stats.record(&cache_key, CacheEvent::Refresh);
+        Ok(true)
+    }
+
+    /// Run [`Self::refresh_search`] without waiting for it
+    fn spawn_search_refresh(&self, request: SearchRequest) {
+        let service = self.clone();
+        // Logged under the request that found the entry stale
+        tokio::spawn(
+            async move {
+                let name = service.marketplace.display_name();
+                match service.refresh_search(&request).await {
+                    Ok(true) => info!("Refreshed cached {name} results for '{}'", request.query),
+                    Ok(false) => {}
+                    Err(e) => warn!("Background refresh of {name} results for '{}' failed: {e}", request.query),
+                }
+            }
+            .in_current_span(),
+        );
+    }
+
+    /// Scrape the requested pages and cache them when they answer the request
+    ///
+    /// Returns every product scraped, the caller applies the limit.
+    async fn scrape_and_cache(&self, request: &SearchRequest, cache_key: &str) -> Result<SearchResults> {
+        let query = request.query.as_str();
+        let limit = request.limit;
+        let results = self.scrape_pages(request).await?;
+
+        if results.products.is_empty() {
+            warn!("No products extracted");
+        } else {
+            info!("Successfully extracted {} products from pages {:?}", results.products.len(), results.pages_fetched);
+            for (i, p) in results.products.iter().enumerate().take(5) {
+                debug!("{}. {} - {}", i + 1, p.name, p.price);
+            }
+        }
+
+        // Only cache if we have sufficient results (at least the requested limit, or every
+        // page the marketplace has). This prevents caching empty or incomplete results
+        let should_cache = !results.products.is_empty() && (results.products.len() >= limit || results.exhausted);
+
+        if should_cache {
+            let ttl = settings().cache.search_ttl_secs;
+            if self.cache_set(cache_key, &results, ttl).await {
+                info!("Cached {} products for query: {query} (TTL: {ttl}s)", results.products.len());
+            }
+        } else {
+            warn!("Not caching: found {} products but requested {}", results.products.len(), limit);
+        }
+
+        Ok(results)
+    }
+
+    /// Read and decode a cached value the request's policy accepts
+    ///
+    /// Records misses, entries too old for `max_age` and `fresh` bypasses; the caller
+    /// records the hit once it knows the value answers the request. Cache failures
+    /// count as a miss.
+    #[instrument(level = "debug", skip(self, policy))]
+    async fn cache_get<T: DeserializeOwned>(&self, key: &str, policy: &CachePolicy) -> Option<CachedEntry<T>> {
+        if policy.fresh {
+            debug!("Skipping cache for {key} (fresh=true)");
+            self.cache_stats.record(key, CacheEvent::Bypassed);
+            return None;
+        }
+
+        let cache_start = Instant::now();
+        let cached = self.cache.get(key).await.unwrap_or_else(|e| {
+            warn!("{} cache read failed: {e}. Continuing without cache.", self.cache.name());
+            None
+        });
+        debug!("{} cache GET: {:?}", self.cache.name(), cache_start.elapsed());
+
+        let Some(entry) = cached.and_then(|cached| serde_json::from_str::<CachedEntry<T>>(&cached).ok()) else {
+            self.cache_stats.record(key, CacheEvent::Miss);
+            return None;
+        };
+        if let Some(max_age) = policy.max_age.filter(|max_age| entry.age() > *max_age) {
+            info!("Cached entry is {:?} old, older than max_age {max_age:?}", entry.age());
+            self.cache_stats.record(key, CacheEvent::TooOld);
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Encode and store a value, reporting whether it was cached
+    #[instrument(level = "debug", skip(self, value))]
+    async fn cache_set<T: Serialize>(&self, key: &str, value: &T, ttl_secs: u64) -> bool {
+        let Ok(json) = serde_json::to_string(&CachedEntry::new(value)) else {
+            return false;
+        };
+        match self.cache.set(key, &json, Duration::from_secs(ttl_secs)).await {
+            Ok(()) => {
+                self.cache_stats.record(key, CacheEvent::Write);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to cache result: {e}");
+                false
+            }
+        }
+    }
+
+    /// Follow the marketplace's pagination until `limit` products are collected
+    ///
+    /// Stops early when a page adds no new products. A failing page after the
+    /// first one ends pagination with the products collected so far.
+    async fn scrape_pages(&self, request: &SearchRequest) -> Result<SearchResults> {
+        let mut results = SearchResults::default();
+        let mut seen_urls = HashSet::new();
+        let last_page = request.page + request.max_pages - 1;
+
+        let search_settings = &settings().search;
+        for page in request.page..=last_page {
+            let search = retry_blocked(search_settings.block_retries, search_settings.block_retry_delay(), || {
+                self.marketplace.search(&self.page_sources, request.mode, &request.query, &request.filters, page)
+            });
+            let page_products = match search.instrument(info_span!("page", page)).await {
+                Ok(products) => products,
+                Err(e) if !results.pages_fetched.is_empty() => {
+                    warn!("Page {page} failed ({e}), keeping {} products from earlier pages", results.products.len());
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            results.pages_fetched.push(page);
+
+            // Drop products already seen on an earlier page (products without URL are kept)
+            let page_products: Vec<Product> = page_products
+                .into_iter()
+                .filter(|p| p.product_url.is_empty() || seen_urls.insert(p.product_url.clone()))
+                .collect();
+            let new_products = page_products.len();
+            results.products.extend(page_products.into_iter().filter(|p| request.filters.accepts(p)));
+            info!("Page {page}: {new_products} new products ({} total)", results.products.len());
+            if let Some(on_page) = &self.on_page {
+                on_page(self.marketplace.id(), page, results.products.len());
+            }
+
+            if new_products == 0 {
+                results.exhausted = true;
+                break;
+            }
+            if results.products.len() >= request.limit {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Scrape the details of a single product page
+    #[instrument(name = "product", skip_all, fields(marketplace = self.marketplace.id(), url))]
+    pub async fn product_detail(&self, url: &str, policy: &CachePolicy) -> Result<Option<ProductDetail>> {
+        let start = Instant::now();
+        info!("Fetching {} product page: {url}", self.marketplace.display_name());
+
+        let cache_key = cache::product_key(self.marketplace.id(), url);
+
+        if let Some(entry) = self.cache_get::<ProductDetail>(&cache_key, policy).await {
+            let age = entry.age();
+            if age > settings().cache.product_soft_ttl() {
+                self.cache_stats.record(&cache_key, CacheEvent::Stale);
+                info!("Cached product is {age:?} old, refreshing it in the background");
+                self.spawn_product_refresh(url.to_string());
+            } else {
+                self.cache_stats.record(&cache_key, CacheEvent::Hit);
+            }
+            info!("Cache hit for product: {url} (total: {:?})", start.elapsed());
+            return Ok(Some(entry.value));
+        }
+
+        self.scrape_and_cache_product(url, &cache_key).await
+    }
+
+    /// Run a product page scrape in the background and replace its cached entry,
+    /// unless a refresh of it is already running
+    fn spawn_product_refresh(&self, url: String) {
+        let cache_key = cache::product_key(self.marketplace.id(), &url);
+        let Some(refresh) = self.refreshes.start(&cache_key) else {
+            debug!("Refresh of {cache_key} already running");
+            return;
+        };
+        let service = self.clone();
+        tokio::spawn(
+            async move {
+                let _refresh = refresh;
+                match service.scrape_and_cache_product(&url, &cache_key).await {
+                    Ok(Some(_)) => {
+                        service.cache_stats.record(&cache_key, CacheEvent::Refresh);
+                        info!("Refreshed cached product: {url}");
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Background refresh of product {url} failed: {e}"),
+                }
+            }
+            .in_current_span(),
+        );
+    }
+
+    async fn scrape_and_cache_product(&self, url: &str, cache_key: &str) -> Result<Option<ProductDetail>> {
+        let search_settings = &settings().search;
+        let fetch = retry_blocked(search_settings.block_retries, search_settings.block_retry_delay(), || {
+            self.marketplace.product_detail(self.page_sources.browser.as_ref(), url)
+        });
+        let Some(detail) = fetch.await? else {
+            warn!("No product details found on page");
+            return Ok(None);
+        };
+        info!(
+            "Extracted '{}' - {} ({} images, {} variants)",
+            detail.name,
+            detail.price,
+            detail.images.len(),
+            detail.variants.len()
+        );
+
+        let ttl = settings().cache.product_ttl_secs;
+        if self.cache_set(cache_key, &detail, ttl).await {
+            info!("Cached product: {url} (TTL: {ttl}s)");
+        }
+
+        Ok(Some(detail))
+    }
+
+    /// Search several marketplaces concurrently and merge their results
+    ///
+    /// A failing marketplace does not fail the whole search: its error is
+    /// reported next to the products of the marketplaces that succeeded.
+    pub async fn search_all(
+        marketplaces: Vec<Arc<dyn Marketplace>>,
+        state: &AppState,
+        request: &SearchRequest,
+        on_page: Option<PageProgress>,
+    ) -> AggregatedSearchResult {
+        let tasks: Vec<_> = marketplaces
+            .into_iter()
+            .map(|marketplace| {
+                let source = marketplace.id().to_string();
+                let service = MarketplaceService::new(marketplace, state).with_progress(on_page.clone());
+                let request = request.clone();
+                let task = tokio::spawn(async move { service.search_products(&request).await }.in_current_span());
+                (source, task)
+            })
+            .collect();
+
+        let mut result = AggregatedSearchResult {
+            products: Vec::new(),
+            errors: Vec::new(),
+            pages_fetched: BTreeMap::new(),
+        };
+
+        for (source, task) in tasks {
+            let error = match task.await {
+                Ok(Ok(results)) => {
+                    info!("{source}: {} products", results.products.len());
+                    result.products.extend(results.products);
+                    result.pages_fetched.insert(source, results.pages_fetched);
+                    continue;
+                }
+                Ok(Err(e)) => ScraperError::from(e),
+                Err(e) => ScraperError::Internal(format!("Scrape task failed: {e}")),
+            };
+            error!(error_code = error.code(), "{source} failed during aggregated search: {error}");
+            result.errors.push(SourceError { source, error_code: error.code().to_string(), error: error.to_string() });
+        }
+
+        result
+    }
+}
+
+/// Run `fetch` again while it fails with [`ScraperError::Blocked`], at most `retries`
+/// more times and waiting `delay`, then twice as long, between attempts
+async fn retry_blocked<T, F, Fut>(retries: u32, delay: Duration, mut fetch: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match fetch().await {
+            Err(e) if attempt < retries && matches!(ScraperError::find(&e), Some(ScraperError::Blocked(_))) => {
+                let wait = delay * (1 << attempt.min(6));
+                attempt += 1;
+                warn!("{e:#}, retrying in {wait:?} ({attempt}/{retries})");
+                tokio::time::sleep(wait).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::marketplace_model::SearchFilters;
+    use crate::page_source::test_support::search_page_state;
+    use crate::tokopedia::TokopediaMarketplace;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_stale_search_is_served_and_refreshed_in_background() {
+        let state = search_page_state();
+        let service = MarketplaceService::new(Arc::new(TokopediaMarketplace), &state);
+
+        // One product cached two soft TTLs ago
+        let request = SearchRequest::new("iphone".to_string(), 1, None, Some(1), SearchFilters::default());
+        let key = cache::search_key("tokopedia", "iphone", &request.filters, 1);
+        let soft_ttl = settings().cache.search_soft_ttl_secs;
+        let mut stale = CachedEntry::new(SearchResults {
+            products: vec![service.scrape_pages(&request).await.unwrap().products.remove(0)],
+            pages_fetched: vec![1],
+            exhausted: true,
+        });
+        stale.stored_at -= 2 * soft_ttl;
+        let json = serde_json::to_string(&stale).unwrap();
+        state.cache.set(&key, &json, Duration::from_secs(60)).await.unwrap();
+
+        let results = service.search_products(&request).await.unwrap();
+        assert_eq!(results.products.len(), 1);
+        assert_eq!(state.cache_stats.snapshot()["search:tokopedia"].stale, 1);
+
+        // The refresh replaces the entry with the full first page
+        for _ in 0..100 {
+            if state.refreshes.running() == 0 && state.cache_stats.snapshot()["search:tokopedia"].refreshes == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let cached = state.cache.get(&key).await.unwrap().unwrap();
+        let entry: CachedEntry<SearchResults> = serde_json::from_str(&cached).unwrap();
+        assert!(entry.age().as_secs() < soft_ttl, "entry was not refreshed");
+        assert_eq!(entry.value.products.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fractional_rating_min_is_applied_to_parsed_products() {
+        let state = search_page_state();
+        let service = MarketplaceService::new(Arc::new(TokopediaMarketplace), &state);
+
+        let filters = SearchFilters { rating_min: Some(4.8), ..SearchFilters::default() };
+        let request = SearchRequest::new("iphone".to_string(), 1, None, Some(10), filters);
+        let products = service.scrape_pages(&request).await.unwrap().products;
+        assert!(!products.is_empty());
+        assert!(products.iter().all(|p| p.rating.is_some_and(|rating| rating >= 4.8)), "{products:?}");
+
+        let filters = SearchFilters { rating_min: Some(4.0), ..SearchFilters::default() };
+        let request = SearchRequest::new("iphone".to_string(), 1, None, Some(10), filters);
+        assert_eq!(service.scrape_pages(&request).await.unwrap().products.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_only_blocked_pages_are_retried() {
+        // Fails with a block until `blocked_attempts` attempts were made
+        let attempts = AtomicU32::new(0);
+        let fetch = |blocked_attempts: u32| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt <= blocked_attempts {
+                    Err(ScraperError::Blocked("captcha page".to_string()).into())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        };
+
+        let delay = Duration::from_millis(1);
+        assert_eq!(retry_blocked(2, delay, || fetch(2)).await.unwrap(), 3);
+        attempts.store(0, Ordering::SeqCst);
+        assert!(retry_blocked(2, delay, || fetch(3)).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Other failures are returned right away
+        attempts.store(0, Ordering::SeqCst);
+        let failing = || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(anyhow::anyhow!("HTTP 500")) }
+        };
+        assert!(retry_blocked(2, delay, failing).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,315 @@
+pub mod marketplace_controller;
+pub mod marketplace_dto;
+pub mod marketplace_model;
+pub mod marketplace_registry;
+pub mod marketplace_service;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Router, routing::get};
+use std::time::Instant;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::block_detection::{PageKind, PageSignatures};
+use crate::error::ScraperError;
+use crate::marketplace::marketplace_model::{FetchMode, Product, ProductDetail, SearchFilters};
+use crate::metrics::metrics;
+use crate::page_source::{Page, PageSource, PageSources, RenderOptions};
+use crate::state::AppState;
+
+/// A scraper source such as Tokopedia or Blibli
+///
+/// Implementors only describe what differs between marketplaces: how the search
+/// URL is built, how pages have to be rendered and how their HTML is parsed.
+/// Page fetching (see [`PageSource`]), caching and routing are shared.
+#[async_trait]
+pub trait Marketplace: Send + Sync {
+    /// Identifier used in routes and cache keys, e.g. `tokopedia`
+    fn id(&self) -> &'static str;
+
+    /// Human readable marketplace name
+    fn display_name(&self) -> &'static str;
+
+    /// Registrable domain of the marketplace, e.g. `tokopedia.com`
+    fn domain(&self) -> &'static str;
+
+    /// Whether a URL points to this marketplace (subdomains included)
+    fn owns_url(&self, url: &Url) -> bool {
+        let domain = self.domain();
+        url.host_str().is_some_and(|host| {
+            host == domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.'))
+        })
+    }
+
+    /// Build the URL of one search results page (1-based) for a query
+    ///
+    /// Filters the marketplace does not support are ignored.
+    fn build_search_url(&self, query: &str, filters: &SearchFilters, page: u32) -> String;
+
+    /// How the search page has to be rendered before it can be parsed
+    fn render_options(&self) -> RenderOptions;
+
+    /// Extract at most `limit` products from a search results page
+    fn parse(&self, html: &str, limit: usize) -> Vec<Product>;
+
+    /// How a product page has to be rendered before it can be parsed
+    fn product_render_options(&self) -> RenderOptions;
+
+    /// Extract the details of a product page, `None` when the page holds no product
+    fn parse_product_detail(&self, html: &str, url: &str) -> Option<ProductDetail>;
+
+    /// Whether search results are embedded in the server-rendered page, so plain
+    /// HTTP can be used instead of a browser (see [`Marketplace::parse_embedded`])
+    fn supports_http_mode(&self) -> bool {
+        false
+    }
+
+    /// Extract at most `limit` products from the data embedded in a search page
+    /// fetched without a browser
+    ///
+    /// `None` means the embedded results are missing, e.g. on a block page.
+    fn parse_embedded(&self, _html: &str, _limit: usize) -> Option<Vec<Product>> {
+        None
+    }
+
+    /// Text identifying the marketplace's empty, block, captcha and error pages,
+    /// on top of the [`COMMON_SIGNATURES`] of bot protection vendors
+    ///
+    /// [`COMMON_SIGNATURES`]: crate::block_detection::COMMON_SIGNATURES
+    fn page_signatures(&self) -> &'static PageSignatures {
+        &PageSignatures::NONE
+    }
+
+    /// Classify a fetched page, failing when it is a block, captcha or error page
+    ///
+    /// Blocks, captchas and unrecognized pages fail with [`ScraperError::Blocked`],
+    /// so they are retried and never cached instead of passing for an empty result.
+    /// They count against the proxy the page came through, any other page for it.
+    fn check_page(&self, url: &str, page: &Page, products: usize) -> Result<PageKind> {
+        self.check_page_kind(url, page, PageKind::classify(&page.html, products, self.page_signatures()))
+    }
+
+    /// Record what a fetched page was classified as, failing like [`Marketplace::check_page`]
+    fn check_page_kind(&self, url: &str, page: &Page, kind: PageKind) -> Result<PageKind> {
+        metrics().pages.with_label_values(&[self.id(), kind.as_str()]).inc();
+        match kind {
+            PageKind::Results | PageKind::Empty => {
+                if let Some(proxy) = &page.proxy {
+                    proxy.record_success();
+                }
+                Ok(kind)
+            }
+            PageKind::Blocked | PageKind::Captcha | PageKind::Unrecognized => {
+                if let Some(proxy) = &page.proxy {
+                    proxy.record_failure(kind.as_str());
+                }
+                Err(ScraperError::Blocked(format!("{} answered {url} with a page classified as {}", self.display_name(), kind.as_str()))
+                    .into())
+            }
+            PageKind::ErrorPage => {
+                Err(ScraperError::ParseFailure(format!("{} answered {url} with an error page", self.display_name())).into())
+            }
+        }
+    }
+
+    /// Fetch and parse every product on one search results page
+    ///
+    /// In [`FetchMode::Auto`] a plain HTTP fetch is tried first when the marketplace
+    /// embeds its results, falling back to the browser when that fails.
+    async fn search(
+        &self,
+        sources: &PageSources,
+        mode: FetchMode,
+        query: &str,
+        filters: &SearchFilters,
+        page: u32,
+    ) -> Result<Vec<Product>> {
+        let url = self.build_search_url(query, filters, page);
+
+        if mode == FetchMode::Http && !self.supports_http_mode() {
+            return Err(ScraperError::InvalidRequest(format!(
+                "{} does not support mode=http, its results need a browser",
+                self.display_name()
+            ))
+            .into());
+        }
+        if mode != FetchMode::Browser && self.supports_http_mode() {
+            match self.search_http(sources.http.as_ref(), &url).await {
+                Ok(products) => return Ok(products),
+                Err(e) if mode == FetchMode::Http => return Err(e),
+                Err(e) => warn!("{e}, falling back to the browser"),
+            }
+        }
+
+        info!("Navigating to {url}");
+        let page = sources.browser.fetch(&url, &self.render_options()).await?;
+        info!("Got page content ({} bytes)", page.html.len());
+
+        let parse_start = Instant::now();
+        let products = self.parse(&page.html, usize::MAX);
+        record_parse(self.id(), parse_start, products.len());
+        self.check_page(&url, &page, products.len())?;
+        Ok(products)
+    }
+
+    /// Fetch a search page without a browser and read its embedded results
+    async fn search_http(&self, source: &dyn PageSource, url: &str) -> Result<Vec<Product>> {
+        info!("Fetching {url} over plain HTTP");
+        let fetch_start = Instant::now();
+        let page = source.fetch(url, &self.render_options()).await?;
+        metrics().observe_phase(self.id(), "fetch", fetch_start);
+        info!("Got page content ({} bytes)", page.html.len());
+
+        let parse_start = Instant::now();
+        let Some(products) = self.parse_embedded(&page.html, usize::MAX) else {
+            self.check_page(url, &page, 0)?;
+            return Err(ScraperError::ParseFailure(format!("No embedded search results in {url}")).into());
+        };
+        record_parse(self.id(), parse_start, products.len());
+        self.check_page(url, &page, products.len())?;
+        Ok(products)
+    }
+
+    /// Fetch and parse a single product page
+    async fn product_detail(&self, source: &dyn PageSource, url: &str) -> Result<Option<ProductDetail>> {
+        let page = source.fetch(url, &self.product_render_options()).await?;
+        info!("Got product page content ({} bytes)", page.html.len());
+
+        let detail = self.parse_product_detail(&page.html, url);
+        let priced = detail.as_ref().is_some_and(|detail| detail.price_idr > 0);
+        let kind = PageKind::classify_product(&page.html, detail.is_some(), priced, self.page_signatures());
+        self.check_page_kind(url, &page, kind)?;
+        Ok(detail)
+    }
+}
+
+/// Record the parsing time and product count of a search page
+fn record_parse(marketplace: &str, start: Instant, products: usize) {
+    metrics().observe_phase(marketplace, "parse", start);
+    metrics().products_extracted.with_label_values(&[marketplace]).inc_by(products as u64);
+}
+
+/// Create router for the generic marketplace scraper endpoints
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/scraper/search", get(marketplace_controller::aggregated_search_handler))
+        .route("/api/scraper/product", get(marketplace_controller::product_detail_handler))
+        .route("/api/scraper/:source", get(marketplace_controller::search_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use crate::blibli::BlibliMarketplace;
+    use crate::tokopedia::TokopediaMarketplace;
+    use std::sync::Arc;
+
+    /// Serves one saved page for every URL, or fails when it has none
+    struct StaticSource(Option<&'static str>);
+
+    #[async_trait]
+    impl PageSource for StaticSource {
+        fn name(&self) -> &'static str {
+            "static"
+        }
+
+        async fn fetch(&self, url: &str, _options: &RenderOptions) -> Result<Page> {
+            match self.0 {
+                Some(html) => Ok(html.to_string().into()),
+                None => bail!("{url} responded with HTTP 403"),
+            }
+        }
+    }
+
+    const NEXT_DATA_PAGE: &str = include_str!("../../fixtures/www.tokopedia.com_search_st_product_q_iphone_2015.html");
+    const DOM_ONLY_PAGE: &str = include_str!("../../fixtures/www.tokopedia.com_search_st_product_q_sepatu_20lari.html");
+
+    fn page_sources(http: Option<&'static str>, browser: Option<&'static str>) -> PageSources {
+        PageSources {
+            browser: Arc::new(StaticSource(browser)),
+            http: Arc::new(StaticSource(http)),
+        }
+    }
+
+    async fn search(marketplace: &dyn Marketplace, sources: &PageSources, mode: FetchMode) -> Result<Vec<Product>> {
+        marketplace.search(sources, mode, "iphone", &SearchFilters::default(), 1).await
+    }
+
+    #[tokio::test]
+    async fn test_auto_mode_uses_embedded_results_without_browser() {
+        let sources = page_sources(Some(NEXT_DATA_PAGE), None);
+        let products = search(&TokopediaMarketplace, &sources, FetchMode::Auto).await.unwrap();
+        assert_eq!(products.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_auto_mode_falls_back_to_browser() {
+        // Embedded results missing from the page
+        let sources = page_sources(Some(DOM_ONLY_PAGE), Some(DOM_ONLY_PAGE));
+        let products = search(&TokopediaMarketplace, &sources, FetchMode::Auto).await.unwrap();
+        assert_eq!(products.len(), 2);
+
+        // Plain HTTP request blocked
+        let sources = page_sources(None, Some(DOM_ONLY_PAGE));
+        let products = search(&TokopediaMarketplace, &sources, FetchMode::Auto).await.unwrap();
+        assert_eq!(products.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_http_mode_never_uses_browser() {
+        let sources = page_sources(Some(DOM_ONLY_PAGE), Some(NEXT_DATA_PAGE));
+        assert!(search(&TokopediaMarketplace, &sources, FetchMode::Http).await.is_err());
+        assert!(search(&BlibliMarketplace, &sources, FetchMode::Http).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_browser_mode_skips_http() {
+        let sources = page_sources(None, Some(DOM_ONLY_PAGE));
+        let products = search(&TokopediaMarketplace, &sources, FetchMode::Browser).await.unwrap();
+        assert_eq!(products.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_block_page_is_not_an_empty_result() {
+        const CAPTCHA_PAGE: &str = r#"<html><div id="px-captcha"></div></html>"#;
+        const EMPTY_PAGE: &str = "<html><h2>Oops, produk nggak ditemukan</h2></html>";
+
+        let sources = page_sources(Some(CAPTCHA_PAGE), Some(CAPTCHA_PAGE));
+        let error = search(&TokopediaMarketplace, &sources, FetchMode::Auto).await.unwrap_err();
+        assert!(matches!(ScraperError::find(&error), Some(ScraperError::Blocked(_))), "{error:#}");
+
+        let sources = page_sources(None, Some(EMPTY_PAGE));
+        assert!(search(&TokopediaMarketplace, &sources, FetchMode::Browser).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unrecognized_page_is_not_an_empty_result() {
+        const INTERSTITIAL_PAGE: &str = "<html><title>One moment, please...</title></html>";
+        const NO_MATCHES_PAGE: &str = r#"<html><div data-testid="divSRPContentProducts"></div></html>"#;
+
+        let sources = page_sources(None, Some(INTERSTITIAL_PAGE));
+        let error = search(&TokopediaMarketplace, &sources, FetchMode::Browser).await.unwrap_err();
+        assert!(matches!(ScraperError::find(&error), Some(ScraperError::Blocked(_))), "{error:#}");
+
+        let sources = page_sources(None, Some(NO_MATCHES_PAGE));
+        assert!(search(&TokopediaMarketplace, &sources, FetchMode::Browser).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_product_without_price_on_captcha_page_is_blocked() {
+        const CAPTCHA_PAGE: &str = r#"<html><script type="application/ld+json">{"@type": "Product", "name": "Verify"}</script>
+            <div id="px-captcha"></div></html>"#;
+
+        let detail = TokopediaMarketplace.parse_product_detail(CAPTCHA_PAGE, "https://www.tokopedia.com/shop/iphone");
+        assert_eq!(detail.unwrap().price_idr, 0);
+
+        let sources = page_sources(None, Some(CAPTCHA_PAGE));
+        let error = TokopediaMarketplace
+            .product_detail(sources.browser.as_ref(), "https://www.tokopedia.com/shop/iphone")
+            .await
+            .unwrap_err();
+        assert!(matches!(ScraperError::find(&error), Some(ScraperError::Blocked(_))), "{error:#}");
+    }
+}
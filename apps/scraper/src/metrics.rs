@@ -0,0 +1,202 @@
+use axum::{
+    extract::State,
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::warn;
+
+use crate::cache::CacheStats;
+use crate::state::AppState;
+
+/// Histogram buckets in seconds, from a quick HTTP fetch to a long browser scroll
+const DURATION_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Prometheus metrics of the scraper, shared by the whole process
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Scraping, parsing, cache and browser pool metrics, served by `GET /metrics`
+pub struct Metrics {
+    registry: Registry,
+    /// Seconds per marketplace and phase: `navigate`, `wait`, `scroll`, `fetch` (plain HTTP), `parse`
+    pub scrape_duration: HistogramVec,
+    /// Products parsed from search pages, per marketplace
+    pub products_extracted: IntCounterVec,
+    /// Search pages parsed, per marketplace and parser (`json` or `dom`)
+    pub parses: IntCounterVec,
+    /// Search pages parsed from the DOM because their embedded JSON was unusable
+    pub parse_fallbacks: IntCounterVec,
+    /// Fetched pages per marketplace and [`crate::block_detection::PageKind`]
+    pub pages: IntCounterVec,
+    /// Cache lookups and writes per kind, marketplace and event, see [`crate::cache::CacheEvent`]
+    pub cache_events: IntCounterVec,
+    cache_hit_ratio: GaugeVec,
+    /// Chrome processes that failed to start
+    pub browser_launch_failures: IntCounter,
+    /// Browser tabs currently rendering a page
+    pub active_tabs: IntGauge,
+    /// Times a proxy was set aside after repeated failures, per proxy server
+    pub proxy_quarantines: IntCounterVec,
+    /// API requests per method, route and status
+    pub http_requests: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let histogram = |name: &str, help: &str, labels: &[&str]| {
+            let opts = HistogramOpts::new(name, help).buckets(DURATION_BUCKETS.to_vec());
+            let histogram = HistogramVec::new(opts, labels).expect("valid histogram");
+            registry.register(Box::new(histogram.clone())).expect("metric registered once");
+            histogram
+        };
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter");
+            registry.register(Box::new(counter.clone())).expect("metric registered once");
+            counter
+        };
+
+        let scrape_duration = histogram(
+            "scraper_scrape_duration_seconds",
+            "Time spent per scraping phase: navigate, wait, scroll, fetch (plain HTTP) and parse",
+            &["marketplace", "phase"],
+        );
+        let products_extracted = counter(
+            "scraper_products_extracted_total",
+            "Products parsed from search result pages",
+            &["marketplace"],
+        );
+        let parses = counter("scraper_parses_total", "Search result pages parsed, by parser", &["marketplace", "parser"]);
+        let parse_fallbacks = counter(
+            "scraper_parse_fallbacks_total",
+            "Search result pages parsed from the DOM because their embedded JSON was unusable",
+            &["marketplace"],
+        );
+        let pages = counter(
+            "scraper_pages_total",
+            "Fetched pages by kind: results, empty, blocked, captcha or error_page",
+            &["marketplace", "kind"],
+        );
+        let cache_events = counter(
+            "scraper_cache_events_total",
+            "Cache lookups and writes by outcome",
+            &["kind", "marketplace", "event"],
+        );
+        let proxy_quarantines = counter(
+            "scraper_proxy_quarantines_total",
+            "Times a proxy was set aside after repeated blocks, timeouts or failed connections",
+            &["proxy"],
+        );
+        let http_requests = histogram(
+            "scraper_http_request_duration_seconds",
+            "API request handling time",
+            &["method", "route", "status"],
+        );
+
+        let cache_hit_ratio = GaugeVec::new(
+            Opts::new(
+                "scraper_cache_hit_ratio",
+                "Share of cache lookups answered from the cache (stale answers included) since startup",
+            ),
+            &["kind", "marketplace"],
+        )
+        .expect("valid gauge");
+        registry.register(Box::new(cache_hit_ratio.clone())).expect("metric registered once");
+        let browser_launch_failures =
+            IntCounter::new("scraper_browser_launch_failures_total", "Chrome processes that failed to start")
+                .expect("valid counter");
+        registry.register(Box::new(browser_launch_failures.clone())).expect("metric registered once");
+        let active_tabs =
+            IntGauge::new("scraper_browser_active_tabs", "Browser tabs currently rendering a page").expect("valid gauge");
+        registry.register(Box::new(active_tabs.clone())).expect("metric registered once");
+
+        Self {
+            registry,
+            scrape_duration,
+            products_extracted,
+            parses,
+            parse_fallbacks,
+            pages,
+            cache_events,
+            cache_hit_ratio,
+            browser_launch_failures,
+            active_tabs,
+            proxy_quarantines,
+            http_requests,
+        }
+    }
+
+    /// Record the time since `start` as one `phase` of scraping a `marketplace` page
+    pub fn observe_phase(&self, marketplace: &str, phase: &str, start: Instant) {
+        self.scrape_duration
+            .with_label_values(&[marketplace, phase])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    /// Every metric in the Prometheus text format, with cache hit ratios taken from `cache_stats`
+    pub fn render(&self, cache_stats: &CacheStats) -> String {
+        for (group, stats) in cache_stats.snapshot() {
+            let (kind, marketplace) = group.split_once(':').unwrap_or((&group, ""));
+            if let Some(ratio) = stats.hit_ratio() {
+                self.cache_hit_ratio.with_label_values(&[kind, marketplace]).set(ratio);
+            }
+        }
+
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            warn!("Failed to encode metrics: {e}");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// HTTP handler serving the metrics to Prometheus
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics().render(&state.cache_stats),
+    )
+}
+
+/// Create router for the Prometheus endpoint
+pub fn router() -> Router<AppState> {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheEvent;
+
+    #[test]
+    fn test_metrics_are_rendered_in_prometheus_format() {
+        // Metrics are process-wide, so this test uses a marketplace of its own
+        let metrics = metrics();
+        metrics.products_extracted.with_label_values(&["metrics-test"]).inc_by(3);
+        metrics.observe_phase("metrics-test", "parse", Instant::now());
+
+        let stats = CacheStats::default();
+        stats.record("search:metrics-test:iphone", CacheEvent::Hit);
+        stats.record("search:metrics-test:iphone", CacheEvent::Stale);
+        stats.record("search:metrics-test:laptop", CacheEvent::Miss);
+        stats.record("search:metrics-test:laptop", CacheEvent::Miss);
+
+        let text = metrics.render(&stats);
+        assert!(text.contains("scraper_products_extracted_total{marketplace=\"metrics-test\"} 3"), "{text}");
+        assert!(text.contains("scraper_scrape_duration_seconds_count{marketplace=\"metrics-test\",phase=\"parse\"} 1"));
+        assert!(text.contains("scraper_cache_events_total{event=\"miss\",kind=\"search\",marketplace=\"metrics-test\"} 2"));
+        assert!(text.contains("scraper_cache_hit_ratio{kind=\"search\",marketplace=\"metrics-test\"} 0.5"));
+        assert!(text.contains("# TYPE scraper_browser_active_tabs gauge"));
+    }
+}
@@ -1,4 +1,25 @@
+pub mod alert;
 pub mod blibli;
+pub mod block_detection;
 pub mod browser;
+pub mod browser_pool;
+pub mod cache;
 pub mod config;
+pub mod error;
+pub mod fingerprint;
+pub mod job;
+pub mod logging;
+pub mod marketplace;
+pub mod metrics;
+pub mod outbound_url;
+pub mod page_source;
+#[cfg(test)]
+pub mod parser_fixtures;
+pub mod popularity;
+pub mod pricing;
+pub mod product_detail;
+pub mod proxy_pool;
+pub mod redis_client;
+pub mod state;
 pub mod tokopedia;
+pub mod watch;
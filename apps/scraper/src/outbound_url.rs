@@ -0,0 +1,118 @@
+use anyhow::{anyhow, bail, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::{Host, Url};
+
+/// Addresses an outbound URL's host resolved to when it was checked
+///
+/// Connecting to them instead of resolving the host again keeps a DNS answer
+/// changed after the check (DNS rebinding) from reaching internal addresses.
+#[derive(Debug, Default)]
+pub struct CheckedUrl {
+    /// Domain and its checked addresses, `None` for IP literals and allowed hosts
+    pinned: Option<(String, Vec<SocketAddr>)>,
+}
+
+impl CheckedUrl {
+    /// Make `builder`'s client connect to the checked addresses only
+    pub fn pin(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match &self.pinned {
+            Some((domain, addresses)) => builder.resolve_to_addrs(domain, addresses),
+            None => builder,
+        }
+    }
+}
+
+/// Check a URL taken from an API request before the server sends data to it
+///
+/// It must be http(s), and its host must not resolve to a loopback, private,
+/// link-local or otherwise internal address, so callers cannot reach services
+/// behind the server. Hosts in `allowed_hosts` are accepted wherever they point.
+pub async fn check_outbound_url(url: &str, allowed_hosts: &[String]) -> Result<CheckedUrl> {
+    let parsed = Url::parse(url).map_err(|e| anyhow!("Invalid URL '{url}': {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("'{url}' must be an http or https URL");
+    }
+    let (Some(host), Some(port)) = (parsed.host(), parsed.port_or_known_default()) else {
+        bail!("'{url}' has no host");
+    };
+    let host_name = parsed.host_str().unwrap_or_default().trim_matches(['[', ']']);
+    if allowed_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host_name)) {
+        return Ok(CheckedUrl::default());
+    }
+
+    let (addresses, domain): (Vec<SocketAddr>, _) = match host {
+        Host::Ipv4(ip) => (vec![SocketAddr::new(ip.into(), port)], None),
+        Host::Ipv6(ip) => (vec![SocketAddr::new(ip.into(), port)], None),
+        Host::Domain(domain) => {
+            let addresses = tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| anyhow!("Failed to resolve '{domain}': {e}"))?
+                .collect();
+            (addresses, Some(domain))
+        }
+    };
+    if let Some(internal) = addresses.iter().find(|address| !is_public(&address.ip())) {
+        bail!("'{url}' points to the internal address {}", internal.ip());
+    }
+    Ok(CheckedUrl {
+        pinned: domain.map(|domain| (domain.to_string(), addresses)),
+    })
+}
+
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(&mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    let this_network = first == 0;
+    let shared = first == 100 && (second & 0xc0) == 64;
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || this_network
+        || shared)
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    let unique_local = (first & 0xfe00) == 0xfc00;
+    let link_local = (first & 0xffc0) == 0xfe80;
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || unique_local || link_local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_internal_targets_are_refused() {
+        for url in [
+            "http://127.0.0.1:8080/done",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5/hook",
+            "http://192.168.1.1/hook",
+            "http://[::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://localhost:4103/api/scraper/cache",
+            "ftp://93.184.216.34/hook",
+        ] {
+            assert!(check_outbound_url(url, &[]).await.is_err(), "{url}");
+        }
+
+        assert!(check_outbound_url("https://93.184.216.34/hook", &[]).await.is_ok());
+        let allowed = ["127.0.0.1".to_string(), "::1".to_string()];
+        assert!(check_outbound_url("http://127.0.0.1:8080/done", &allowed).await.is_ok());
+        assert!(check_outbound_url("http://[::1]/hook", &allowed).await.is_ok());
+    }
+}
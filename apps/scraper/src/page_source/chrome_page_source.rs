@@ -0,0 +1,149 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use headless_chrome::Tab;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use tracing::{debug, info, info_span, warn, Span};
+
+use crate::browser_pool::BrowserPool;
+use crate::config::get_page_load_timeout;
+use crate::error::ScraperError;
+use crate::fingerprint::{FingerprintProfile, Fingerprints};
+use crate::metrics::metrics;
+use crate::page_source::{Page, PageSource, RenderOptions};
+
+/// Pages rendered in a pooled Chrome tab: navigate, wait, scroll and read the HTML
+///
+/// `headless_chrome` is synchronous, so the rendering runs on tokio's blocking
+/// thread pool and never stalls the async workers serving other requests.
+/// Every page is loaded with the next fingerprint profile.
+pub struct ChromePageSource {
+    browser_pool: Arc<BrowserPool>,
+    fingerprints: Arc<Fingerprints>,
+}
+
+impl ChromePageSource {
+    pub fn new(browser_pool: Arc<BrowserPool>, fingerprints: Arc<Fingerprints>) -> Self {
+        Self { browser_pool, fingerprints }
+    }
+}
+
+#[async_trait]
+impl PageSource for ChromePageSource {
+    fn name(&self) -> &'static str {
+        "chrome"
+    }
+
+    async fn fetch(&self, url: &str, options: &RenderOptions) -> Result<Page> {
+        debug!("Acquiring browser tab from pool...");
+        let tab = self.browser_pool.acquire_tab().await?;
+        let proxy = tab.proxy();
+        debug!("Browser tab acquired successfully");
+
+        // The tab moves along, so it also goes back to the pool off the async workers.
+        // Blocking threads don't inherit the caller's span, so it is carried over
+        let url = url.to_string();
+        let options = *options;
+        let profile = self.fingerprints.next();
+        let fingerprint = profile.map_or("default", |profile| profile.name);
+        let span = info_span!(parent: Span::current(), "browser", url = %url, fingerprint);
+        let rendered = tokio::task::spawn_blocking(move || span.in_scope(|| render(&tab, &url, options, profile)))
+            .await
+            .context("Browser task panicked")?;
+
+        match rendered {
+            Ok(html) => Ok(Page { html, proxy }),
+            Err(e) => {
+                // Mostly pages that never loaded: timeouts and refused connections
+                if let Some(proxy) = &proxy {
+                    proxy.record_failure(&format!("{e:#}"));
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Load `url` as `profile` and wait until its results have rendered, without fixed delays
+fn render(tab: &Tab, url: &str, options: RenderOptions, profile: Option<&FingerprintProfile>) -> Result<String> {
+    let start = Instant::now();
+    let marketplace = options.marketplace;
+    tab.set_default_timeout(get_page_load_timeout());
+    if let Some(profile) = profile {
+        profile.apply(tab)?;
+    }
+
+    let phase = info_span!("navigate").entered();
+    info!("Navigating to: {}", url);
+    tab.navigate_to(url)
+        .context("Failed to navigate to URL")?
+        .wait_until_navigated()
+        .map_err(|e| ScraperError::NavigationTimeout(format!("Page failed to load: {e}")))?;
+    metrics().observe_phase(marketplace, "navigate", start);
+    match tab.get_title() {
+        Ok(title) => info!("Page loaded after {:.1}s: {title}", start.elapsed().as_secs_f32()),
+        Err(e) => warn!("Could not get page title: {e}"),
+    }
+    drop(phase);
+
+    // Scripts render the results after the load event, wait for the first ones to appear
+    let phase = info_span!("wait").entered();
+    debug!("Waiting for results ({})...", options.ready_selector);
+    let wait_start = Instant::now();
+    match tab.wait_for_element_with_custom_timeout(options.ready_selector, options.timing.content_ready_timeout()) {
+        Ok(_) => info!("Results ready after {:.1}s", start.elapsed().as_secs_f32()),
+        Err(_) => warn!("Timeout waiting for results, proceeding with what we have..."),
+    }
+    metrics().observe_phase(marketplace, "wait", wait_start);
+    drop(phase);
+
+    // Dynamic scrolling: continue until a scroll no longer loads new products
+    let phase = info_span!("scroll").entered();
+    debug!("Starting dynamic scroll to load all products...");
+    let scroll_start = Instant::now();
+    let mut count = product_count(tab, options.product_count_script);
+
+    let max_scroll_attempts = options.timing.max_scroll_attempts;
+    for scroll_attempt in 1..=max_scroll_attempts {
+        let _ = tab.evaluate("window.scrollTo(0, document.body.scrollHeight);", false);
+
+        let current_count = wait_for_more_products(tab, &options, count);
+        debug!("Scroll {scroll_attempt}/{max_scroll_attempts}: {current_count} products detected");
+
+        if current_count <= count {
+            info!("Product count stable at {current_count}, stopping scroll");
+            break;
+        }
+        count = current_count;
+    }
+
+    // Scroll back to top to ensure all elements are in DOM
+    let _ = tab.evaluate("window.scrollTo(0, 0);", false);
+    metrics().observe_phase(marketplace, "scroll", scroll_start);
+    drop(phase);
+
+    info!("Rendering complete after {:.1}s, extracting products...", start.elapsed().as_secs_f32());
+    tab.get_content().context("Failed to get page content")
+}
+
+/// Number of products the page reports through its count script
+fn product_count(tab: &Tab, count_script: &str) -> usize {
+    match tab.evaluate(count_script, false) {
+        Ok(obj) => obj.value.and_then(|v| v.as_i64()).unwrap_or(0) as usize,
+        Err(_) => 0,
+    }
+}
+
+/// Poll the product count until it grows past `previous` or the page stays quiet
+/// for the scroll settle time
+fn wait_for_more_products(tab: &Tab, options: &RenderOptions, previous: usize) -> usize {
+    let deadline = Instant::now() + options.timing.scroll_settle();
+    loop {
+        let count = product_count(tab, options.product_count_script);
+        if count > previous || Instant::now() >= deadline {
+            return count;
+        }
+        thread::sleep(options.timing.poll_interval());
+    }
+}
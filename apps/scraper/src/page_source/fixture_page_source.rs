@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tracing::debug;
+
+use crate::page_source::{Page, PageSource, RenderOptions};
+
+/// Pages read from HTML files stored on disk, one file per URL
+///
+/// Lets stored pages be reparsed, and the whole API run, on machines without
+/// Chrome or network access. See [`FixturePageSource::file_name`] for how URLs
+/// map to files.
+pub struct FixturePageSource {
+    dir: PathBuf,
+}
+
+impl FixturePageSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// File name a URL is stored under: scheme dropped, every character other
+    /// than ASCII letters, digits, `-` and `.` replaced by `_`, plus `.html`
+    ///
+    /// `https://www.tokopedia.com/search?st=product&q=iphone` becomes
+    /// `www.tokopedia.com_search_st_product_q_iphone.html`.
+    pub fn file_name(url: &str) -> String {
+        let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let name: String = without_scheme
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        format!("{}.html", name.trim_matches('_'))
+    }
+
+    /// Path of the fixture for `url`
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(Self::file_name(url))
+    }
+}
+
+#[async_trait]
+impl PageSource for FixturePageSource {
+    fn name(&self) -> &'static str {
+        "fixture"
+    }
+
+    async fn fetch(&self, url: &str, _options: &RenderOptions) -> Result<Page> {
+        let path = self.path_for(url);
+        debug!("Reading fixture {}", path.display());
+        let html = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("No fixture for {url} at {}", path.display()))?;
+        Ok(html.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            FixturePageSource::file_name("https://www.tokopedia.com/search?st=product&q=iphone%2015"),
+            "www.tokopedia.com_search_st_product_q_iphone_2015.html"
+        );
+        assert_eq!(
+            FixturePageSource::file_name("https://www.blibli.com/cari/laptop/"),
+            "www.blibli.com_cari_laptop.html"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_reads_stored_page() {
+        let dir = std::env::temp_dir().join(format!("scraper-fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = "https://www.blibli.com/cari/kaos";
+        std::fs::write(dir.join(FixturePageSource::file_name(url)), "<html>kaos</html>").unwrap();
+
+        let source = FixturePageSource::new(&dir);
+        let options = RenderOptions { marketplace: "blibli", ready_selector: "", product_count_script: "", timing: Default::default() };
+        assert_eq!(source.fetch(url, &options).await.unwrap().html, "<html>kaos</html>");
+        assert!(source.fetch("https://www.blibli.com/cari/other", &options).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
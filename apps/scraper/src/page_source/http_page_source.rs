@@ -0,0 +1,175 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, USER_AGENT};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::config::{get_page_load_timeout, settings};
+use crate::error::ScraperError;
+use crate::fingerprint::Fingerprints;
+use crate::page_source::{Page, PageSource, RenderOptions};
+use crate::proxy_pool::{Proxy, ProxyPool};
+
+/// Pages fetched with a plain HTTP GET, without running any JavaScript
+///
+/// Much cheaper than a browser, but only sees what the server renders: the
+/// embedded `__NEXT_DATA__` and JSON-LD payloads rather than lazy-loaded cards.
+/// With proxies configured every request goes through the next one in rotation,
+/// and it sends the user agent and language of the next fingerprint profile.
+pub struct HttpPageSource {
+    /// Client connecting directly, used without proxies
+    client: reqwest::Client,
+    proxies: Arc<ProxyPool>,
+    /// One client per proxy, in the pool's order
+    proxy_clients: Vec<reqwest::Client>,
+    fingerprints: Arc<Fingerprints>,
+}
+
+impl HttpPageSource {
+    pub fn new(proxies: Arc<ProxyPool>, fingerprints: Arc<Fingerprints>) -> Result<Self> {
+        let client = Self::client_builder().build().context("Failed to build HTTP client")?;
+        let proxy_clients = proxies
+            .proxies()
+            .iter()
+            .map(|proxy| {
+                let builder = Self::client_builder().proxy(reqwest::Proxy::all(proxy.url())?);
+                builder.build().with_context(|| format!("Failed to build HTTP client for proxy {proxy}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { client, proxies, proxy_clients, fingerprints })
+    }
+
+    fn client_builder() -> reqwest::ClientBuilder {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("text/html,application/xhtml+xml"));
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("id-ID,id;q=0.9,en;q=0.8"));
+
+        reqwest::Client::builder()
+            .user_agent(settings().browser.user_agent.as_str())
+            .default_headers(headers)
+            .timeout(get_page_load_timeout())
+    }
+
+    /// The next proxy and its client, the direct client without proxies
+    fn next_client(&self) -> (Option<Arc<Proxy>>, &reqwest::Client) {
+        let Some(proxy) = self.proxies.next() else {
+            return (None, &self.client);
+        };
+        let index = self.proxies.proxies().iter().position(|p| Arc::ptr_eq(p, &proxy));
+        let client = index.map_or(&self.client, |index| &self.proxy_clients[index]);
+        (Some(proxy), client)
+    }
+}
+
+#[async_trait]
+impl PageSource for HttpPageSource {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn fetch(&self, url: &str, _options: &RenderOptions) -> Result<Page> {
+        let (proxy, client) = self.next_client();
+        match &proxy {
+            Some(proxy) => info!("HTTP GET {url} through {proxy}"),
+            None => info!("HTTP GET {url}"),
+        }
+        // Blocks, timeouts and failed connections count against the proxy, the marketplace's own errors don't
+        let proxy_failed = |reason: &str| {
+            if let Some(proxy) = &proxy {
+                proxy.record_failure(reason);
+            }
+        };
+
+        let mut request = client.get(url);
+        if let Some(profile) = self.fingerprints.next() {
+            request = request.header(USER_AGENT, profile.user_agent).header(ACCEPT_LANGUAGE, profile.accept_language);
+        }
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                proxy_failed(&e.to_string());
+                if e.is_timeout() {
+                    return Err(ScraperError::NavigationTimeout(format!("Timed out fetching {url}")).into());
+                }
+                return Err(anyhow::Error::from(e).context(format!("Failed to fetch {url}")));
+            }
+        };
+
+        let status = response.status();
+        if matches!(status, StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS) {
+            proxy_failed(&format!("HTTP {status}"));
+            return Err(ScraperError::Blocked(format!("{url} responded with HTTP {status}")).into());
+        }
+        if !status.is_success() {
+            bail!("{url} responded with HTTP {status}");
+        }
+
+        let html = response.text().await.context("Failed to read response body")?;
+        Ok(Page { html, proxy })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FingerprintSettings, ProxySettings};
+    use axum::{http::HeaderMap, http::Uri, Router};
+    use std::sync::Mutex;
+
+    /// URL and `Proxy-Authorization` of every request a stand-in proxy received
+    type Received = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// Local stand-in for a forward proxy, answering every request with `status`
+    async fn stand_in_proxy(status: StatusCode, received: Received) -> String {
+        let app = Router::new().fallback(move |uri: Uri, headers: HeaderMap| async move {
+            let login = headers.get("proxy-authorization").and_then(|v| v.to_str().ok()).map(String::from);
+            received.lock().unwrap().push((uri.to_string(), login));
+            (status, "<html>through the proxy</html>")
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        address.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_requests_rotate_through_proxies_until_quarantined() {
+        let good = Received::default();
+        let blocked = Received::default();
+        let good_address = stand_in_proxy(StatusCode::OK, good.clone()).await;
+        let blocked_address = stand_in_proxy(StatusCode::TOO_MANY_REQUESTS, blocked.clone()).await;
+        let settings = ProxySettings {
+            urls: vec![format!("http://user:s3cret@{good_address}"), format!("http://{blocked_address}")],
+            quarantine_after: 2,
+            quarantine_secs: 60,
+        };
+        let fingerprints = Fingerprints::new(&FingerprintSettings::default()).unwrap();
+        let source = HttpPageSource::new(Arc::new(ProxyPool::new(&settings).unwrap()), Arc::new(fingerprints)).unwrap();
+        let options = RenderOptions { marketplace: "tokopedia", ready_selector: "", product_count_script: "", timing: Default::default() };
+
+        let url = "http://www.tokopedia.test/search?q=iphone";
+        let mut blocks = 0;
+        for _ in 0..6 {
+            match source.fetch(url, &options).await {
+                Ok(page) => {
+                    assert_eq!(page.html, "<html>through the proxy</html>");
+                    assert_eq!(page.proxy.unwrap().server(), format!("http://{good_address}"));
+                }
+                Err(e) => {
+                    assert!(matches!(ScraperError::find(&e), Some(ScraperError::Blocked(_))), "{e:#}");
+                    blocks += 1;
+                }
+            }
+        }
+
+        // The blocking proxy is set aside after its second block in a row
+        assert_eq!(blocks, 2);
+        assert!(source.proxies.proxies()[1].is_quarantined());
+        assert_eq!(good.lock().unwrap().len(), 4);
+        let (target, login) = good.lock().unwrap()[0].clone();
+        assert_eq!(target, url);
+        assert_eq!(login.as_deref(), Some("Basic dXNlcjpzM2NyZXQ="));
+        assert_eq!(blocked.lock().unwrap()[0].1, None);
+    }
+}
@@ -0,0 +1,100 @@
+pub mod chrome_page_source;
+pub mod fixture_page_source;
+pub mod http_page_source;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::RenderTiming;
+use crate::proxy_pool::Proxy;
+
+pub use chrome_page_source::ChromePageSource;
+pub use fixture_page_source::FixturePageSource;
+pub use http_page_source::HttpPageSource;
+
+/// How a marketplace page has to be rendered before its HTML is complete
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Id of the marketplace the page belongs to, labelling its metrics
+    pub marketplace: &'static str,
+    /// CSS selector that matches once the first results have rendered
+    pub ready_selector: &'static str,
+    /// Script that returns the number of product cards currently rendered
+    pub product_count_script: &'static str,
+    /// How long rendering waits, see the `marketplaces` settings
+    pub timing: RenderTiming,
+}
+
+/// HTML of a fetched page and the proxy it came through
+pub struct Page {
+    pub html: String,
+    /// `None` when the page was fetched directly
+    pub proxy: Option<Arc<Proxy>>,
+}
+
+impl From<String> for Page {
+    fn from(html: String) -> Self {
+        Self { html, proxy: None }
+    }
+}
+
+/// Where page HTML comes from: a browser, a plain HTTP request or stored files
+///
+/// Marketplaces only build URLs and parse HTML, so any source can feed them.
+#[async_trait]
+pub trait PageSource: Send + Sync {
+    /// Short name used in logs, e.g. `chrome`
+    fn name(&self) -> &'static str;
+
+    /// Fetch the HTML of `url`; sources that cannot run scripts ignore `options`
+    ///
+    /// Sources fetching through a proxy report the failures they see to it; blocks
+    /// found in the HTML are reported by [`Marketplace::check_page`].
+    ///
+    /// [`Marketplace::check_page`]: crate::marketplace::Marketplace::check_page
+    async fn fetch(&self, url: &str, options: &RenderOptions) -> Result<Page>;
+}
+
+/// The page sources a request can choose from, see [`FetchMode`]
+///
+/// [`FetchMode`]: crate::marketplace::marketplace_model::FetchMode
+#[derive(Clone)]
+pub struct PageSources {
+    /// Renders pages, normally headless Chrome
+    pub browser: Arc<dyn PageSource>,
+    /// Downloads the server-rendered HTML without running scripts
+    pub http: Arc<dyn PageSource>,
+}
+
+/// Page source and app state shared by service tests
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+    use crate::cache::MemoryCache;
+    use crate::marketplace::marketplace_registry::MarketplaceRegistry;
+    use crate::state::AppState;
+    use crate::tokopedia::TokopediaMarketplace;
+
+    /// Serves the same saved Tokopedia search page for every URL
+    pub struct SearchPage;
+
+    #[async_trait]
+    impl PageSource for SearchPage {
+        fn name(&self) -> &'static str {
+            "static"
+        }
+
+        async fn fetch(&self, _url: &str, _options: &RenderOptions) -> Result<Page> {
+            Ok(include_str!("../../fixtures/www.tokopedia.com_search_st_product_q_iphone_2015.html").to_string().into())
+        }
+    }
+
+    /// State with Tokopedia registered, every page served by [`SearchPage`] and an in-memory cache
+    pub fn search_page_state() -> AppState {
+        let page: Arc<dyn PageSource> = Arc::new(SearchPage);
+        let mut marketplaces = MarketplaceRegistry::default();
+        marketplaces.register(Arc::new(TokopediaMarketplace));
+        AppState::new(PageSources { browser: page.clone(), http: page }, marketplaces, Arc::new(MemoryCache::new(10)))
+    }
+}
@@ -0,0 +1,305 @@
+//! Regression suite over saved search result pages
+//!
+//! Every page in `fixtures/` is parsed offline with the parser of the
+//! marketplace it was saved from and compared field by field with the
+//! `.expected.json` file next to it. Pages are named after their URL as
+//! [`FixturePageSource::file_name`] does, so the same directory also serves
+//! the whole API with `SCRAPER_PAGE_SOURCE=fixture`. Refresh the pages from the
+//! live sites with `cargo test capture_live_fixtures -- --ignored`, then
+//! regenerate the expected output with `UPDATE_FIXTURES=1 cargo test parser_fixtures`
+//! and review the git diff.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use url::Url;
+
+use crate::blibli::BlibliMarketplace;
+use crate::marketplace::Marketplace;
+use crate::page_source::fixture_page_source::FixturePageSource;
+use crate::tokopedia::TokopediaMarketplace;
+
+/// Lines of unchanged context shown around each difference
+const DIFF_CONTEXT: usize = 3;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+fn marketplaces() -> Vec<Arc<dyn Marketplace>> {
+    vec![Arc::new(TokopediaMarketplace), Arc::new(BlibliMarketplace)]
+}
+
+/// Saved pages of one marketplace, told apart by the host their name starts with, sorted by name
+fn fixture_pages(marketplace: &dyn Marketplace) -> Vec<PathBuf> {
+    let dir = fixtures_dir();
+    let mut pages: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("Cannot read fixtures in {}: {e}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let host = name.split('_').next().unwrap_or_default();
+            Url::parse(&format!("https://{host}/")).is_ok_and(|url| marketplace.owns_url(&url))
+        })
+        .collect();
+    pages.sort();
+    pages
+}
+
+fn expected_path(page: &Path) -> PathBuf {
+    page.with_extension("expected.json")
+}
+
+/// Compare one fixture, returning a readable report when it does not match
+fn check_fixture(marketplace: &dyn Marketplace, page: &Path, update: bool) -> Option<String> {
+    let html = fs::read_to_string(page).unwrap();
+    // Serialized as the API does, so e.g. ratings read `4.9` rather than their widened f64
+    let actual = serde_json::to_string_pretty(&marketplace.parse(&html, usize::MAX)).unwrap() + "\n";
+    let expected_path = expected_path(page);
+
+    if update {
+        fs::write(&expected_path, &actual).unwrap();
+        return None;
+    }
+
+    let Ok(expected) = fs::read_to_string(&expected_path) else {
+        return Some(format!(
+            "{}: missing {}, run with UPDATE_FIXTURES=1 to create it",
+            page.display(),
+            expected_path.display()
+        ));
+    };
+
+    // Compare as JSON so formatting changes in the file don't count as failures
+    let expected_value: Value = serde_json::from_str(&expected)
+        .unwrap_or_else(|e| panic!("Invalid JSON in {}: {e}", expected_path.display()));
+    let actual_value: Value = serde_json::from_str(&actual).unwrap();
+    if expected_value == actual_value {
+        return None;
+    }
+
+    Some(format!(
+        "{} no longer matches {} (- expected, + actual):\n{}",
+        page.display(),
+        expected_path.display(),
+        line_diff(&expected, &actual)
+    ))
+}
+
+/// Line-based diff of two texts with a few lines of context around each change
+fn line_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len()).filter(|&k| lines[k].0 != ' ').collect();
+    let mut output = String::new();
+    let mut last_shown = None;
+    for (k, (tag, line)) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&c| k + DIFF_CONTEXT >= c && k <= c + DIFF_CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| last + 1 != k) {
+            output.push_str("  ...\n");
+        }
+        output.push_str(&format!("{tag} {line}\n"));
+        last_shown = Some(k);
+    }
+    output
+}
+
+#[test]
+fn test_search_fixtures_match_expected_output() {
+    let update = std::env::var("UPDATE_FIXTURES").is_ok_and(|v| v == "1");
+    let mut failures = Vec::new();
+
+    for marketplace in marketplaces() {
+        let pages = fixture_pages(marketplace.as_ref());
+        assert!(!pages.is_empty(), "No fixtures for {}", marketplace.id());
+        for page in pages {
+            failures.extend(check_fixture(marketplace.as_ref(), &page, update));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_fixtures_cover_next_data_and_dom_only_pages() {
+    let pages = fixture_pages(&TokopediaMarketplace);
+    let with_next_data = pages
+        .iter()
+        .filter(|page| fs::read_to_string(page).unwrap().contains("__NEXT_DATA__"))
+        .count();
+    assert!(with_next_data > 0, "No Tokopedia fixture with __NEXT_DATA__");
+    assert!(with_next_data < pages.len(), "No DOM-only Tokopedia fixture");
+}
+
+/// Searches saved in `fixtures/`, with whether the page is the server-rendered one fetched over plain HTTP
+fn fixture_searches() -> Vec<(Arc<dyn Marketplace>, &'static str, bool)> {
+    vec![
+        // Server-rendered, results embedded in __NEXT_DATA__
+        (Arc::new(TokopediaMarketplace), "iphone 15", true),
+        // Rendered by Chrome, results read from the DOM
+        (Arc::new(TokopediaMarketplace), "sepatu lari", false),
+        (Arc::new(BlibliMarketplace), "kaos polos", false),
+    ]
+}
+
+#[test]
+fn test_fixtures_are_named_after_search_urls() {
+    for (marketplace, query, _) in fixture_searches() {
+        let url = marketplace.build_search_url(query, &Default::default(), 1);
+        let path = fixtures_dir().join(FixturePageSource::file_name(&url));
+        assert!(path.exists(), "No fixture for {url} at {}", path.display());
+    }
+}
+
+/// Keys of embedded JSON whose values identify the visitor rather than the products
+const PERSONAL_KEYS: &[&str] = &[
+    "userid", "user_id", "uniqueid", "unique_id", "deviceid", "device_id", "sessionid", "session_id",
+    "email", "phone", "msisdn", "address", "token", "accesstoken", "csrf", "csrftoken",
+];
+
+/// Blank the values of [`PERSONAL_KEYS`] anywhere in embedded JSON
+fn redact_personal_values(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if PERSONAL_KEYS.contains(&key.to_lowercase().as_str()) {
+                    *value = Value::String(String::new());
+                } else {
+                    redact_personal_values(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_personal_values),
+        _ => {}
+    }
+}
+
+/// Strip what a captured page says about the visitor before it is committed
+///
+/// Inline scripts carry session, tracking and login state and are dropped,
+/// except `__NEXT_DATA__` (with [`PERSONAL_KEYS`] blanked) and JSON-LD, which
+/// the parsers read. Script tags loading a `src` are kept as they are.
+fn strip_personal_data(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<script") {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("</script>").map(|end| start + end + "</script>".len()) else {
+            rest = &rest[start..];
+            break;
+        };
+        let script = &rest[start..end];
+        let open_tag = &script[..script.find('>').map_or(script.len(), |i| i + 1)];
+        if open_tag.contains("__NEXT_DATA__") {
+            let body = &script[open_tag.len()..script.len() - "</script>".len()];
+            match serde_json::from_str::<Value>(body) {
+                Ok(mut data) => {
+                    redact_personal_values(&mut data);
+                    output.push_str(open_tag);
+                    output.push_str(&serde_json::to_string(&data).unwrap());
+                    output.push_str("</script>");
+                }
+                Err(_) => output.push_str(script),
+            }
+        } else if open_tag.contains("application/ld+json") || open_tag.contains(" src=") {
+            output.push_str(script);
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[test]
+fn test_captured_pages_are_stripped_of_personal_data() {
+    let html = concat!(
+        r#"<html><head><script>window.__session = {"email": "budi@example.com"};</script>"#,
+        r#"<script src="https://assets.tokopedia.net/app.js"></script>"#,
+        r#"<script type="application/ld+json">{"@type": "Product", "name": "Kaos Polos"}</script></head>"#,
+        r#"<body><script id="__NEXT_DATA__" type="application/json">"#,
+        r#"{"props": {"user": {"userId": "12345", "name": "Budi"}, "products": [{"name": "iPhone 15"}]}}</script>"#,
+        "</body></html>",
+    );
+
+    let stripped = strip_personal_data(html);
+    assert!(!stripped.contains("budi@example.com"));
+    assert!(!stripped.contains("12345"));
+    assert!(stripped.contains(r#"<script src="https://assets.tokopedia.net/app.js"></script>"#));
+    assert!(stripped.contains(r#"{"@type": "Product", "name": "Kaos Polos"}"#));
+    assert!(stripped.contains(r#""products":[{"name":"iPhone 15"}]"#));
+    assert!(stripped.ends_with("</body></html>"));
+}
+
+/// Save the live search pages over the fixtures, then regenerate the expected output
+///
+/// Needs network access and Chrome, run with `cargo test capture_live_fixtures -- --ignored`.
+/// Pages are stripped of personal data first, see [`strip_personal_data`].
+#[tokio::test]
+#[ignore = "fetches live marketplace pages"]
+async fn capture_live_fixtures() {
+    use crate::browser_pool::BrowserPool;
+    use crate::config::settings;
+    use crate::fingerprint::Fingerprints;
+    use crate::page_source::chrome_page_source::ChromePageSource;
+    use crate::page_source::http_page_source::HttpPageSource;
+    use crate::page_source::PageSource;
+    use crate::proxy_pool::ProxyPool;
+
+    let proxies = Arc::new(ProxyPool::new(&settings().proxy).unwrap());
+    let fingerprints = Arc::new(Fingerprints::new(&settings().fingerprint).unwrap());
+    let http = HttpPageSource::new(Arc::clone(&proxies), Arc::clone(&fingerprints)).unwrap();
+    let chrome = ChromePageSource::new(Arc::new(BrowserPool::new(1, 1, proxies)), fingerprints);
+
+    for (marketplace, query, via_http) in fixture_searches() {
+        let url = marketplace.build_search_url(query, &Default::default(), 1);
+        let source: &dyn PageSource = if via_http { &http } else { &chrome };
+        let page = source
+            .fetch(&url, &marketplace.render_options())
+            .await
+            .unwrap_or_else(|e| panic!("Failed to capture {url}: {e:#}"));
+        fs::write(fixtures_dir().join(FixturePageSource::file_name(&url)), strip_personal_data(&page.html)).unwrap();
+    }
+}
+
+#[test]
+fn test_line_diff_shows_changed_lines_with_context() {
+    let expected = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+    let actual = "a\nb\nc\nd\ne\nF\ng\nh\ni\n";
+    assert_eq!(line_diff(expected, actual), "  c\n  d\n  e\n- f\n+ F\n  g\n  h\n  i\n");
+}
@@ -0,0 +1,135 @@
+use serde_json::Value;
+
+/// Parse an Indonesian count such as "10rb+ terjual", "1,2rb terjual", "2jt+" or "Terjual 1.234"
+///
+/// `rb` (ribu) multiplies by a thousand and `jt` (juta) by a million, with a comma
+/// or dot before the suffix read as a decimal separator. Without a suffix, dots and
+/// commas are thousand separators. A trailing `+` marks a lower bound, which is
+/// exactly what is returned.
+pub fn parse_count(text: &str) -> Option<u64> {
+    let lower = text.to_lowercase();
+    let start = lower.find(|c: char| c.is_ascii_digit())?;
+    let rest = &lower[start..];
+
+    let number_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(rest.len());
+    let number = rest[..number_len].trim_end_matches(['.', ',']);
+    let suffix = rest[number_len..].trim_start();
+
+    let multiplier = if suffix.starts_with("rb") || suffix.starts_with("ribu") {
+        1_000.0
+    } else if suffix.starts_with("jt") || suffix.starts_with("juta") {
+        1_000_000.0
+    } else {
+        let digits: String = number.chars().filter(char::is_ascii_digit).collect();
+        return digits.parse().ok();
+    };
+
+    let value: f64 = number.replace(',', ".").parse().ok()?;
+    Some((value * multiplier).round() as u64)
+}
+
+/// Parse a sold label, only when it actually mentions sales ("terjual" / "sold")
+pub fn parse_sold_count(text: &str) -> Option<u64> {
+    let lower = text.to_lowercase();
+    if !lower.contains("terjual") && !lower.contains("sold") {
+        return None;
+    }
+    parse_count(&lower)
+}
+
+/// Parse a review count label such as "(120)", "(1,2rb)" or "120 ulasan"
+pub fn parse_review_count(text: &str) -> Option<u64> {
+    let trimmed = text.trim();
+    let lower = trimmed.to_lowercase();
+    let is_review_label = (trimmed.starts_with('(') && trimmed.ends_with(')'))
+        || lower.contains("ulasan")
+        || lower.contains("review");
+    if !is_review_label {
+        return None;
+    }
+    parse_count(trimmed)
+}
+
+/// Parse a rating out of 5 such as "4.8" or "4,8"
+pub fn parse_rating(text: &str) -> Option<f32> {
+    let rating: f32 = text.trim().replace(',', ".").parse().ok()?;
+    (rating > 0.0 && rating <= 5.0).then_some(rating)
+}
+
+/// Read a rating from a JSON number or string
+///
+/// Some payloads use a 0-100 scale, which is converted to 0-5. Values between
+/// 5 and 10 fit neither scale and are rejected.
+pub fn rating_from_json(value: &Value) -> Option<f32> {
+    let rating = match value {
+        Value::Number(n) => n.as_f64()? as f32,
+        Value::String(s) => s.trim().replace(',', ".").parse().ok()?,
+        _ => return None,
+    };
+    if rating > 10.0 && rating <= 100.0 {
+        return Some((rating / 20.0 * 10.0).round() / 10.0);
+    }
+    (rating > 0.0 && rating <= 5.0).then_some(rating)
+}
+
+/// Read a count from a JSON number or an Indonesian count string
+pub fn count_from_json(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => parse_count(s),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_count_abbreviations() {
+        assert_eq!(parse_count("10rb+ terjual"), Some(10_000));
+        assert_eq!(parse_count("1,2rb terjual"), Some(1_200));
+        assert_eq!(parse_count("1.5 rb"), Some(1_500));
+        assert_eq!(parse_count("2jt+"), Some(2_000_000));
+        assert_eq!(parse_count("250+ terjual"), Some(250));
+        assert_eq!(parse_count("Terjual 1.234"), Some(1_234));
+        assert_eq!(parse_count("terjual"), None);
+    }
+
+    #[test]
+    fn test_parse_sold_count_requires_sold_label() {
+        assert_eq!(parse_sold_count("100+ terjual"), Some(100));
+        assert_eq!(parse_sold_count("Jakarta Barat"), None);
+        assert_eq!(parse_sold_count("4.9"), None);
+    }
+
+    #[test]
+    fn test_parse_review_count() {
+        assert_eq!(parse_review_count("(120)"), Some(120));
+        assert_eq!(parse_review_count("(1,2rb)"), Some(1_200));
+        assert_eq!(parse_review_count("85 ulasan"), Some(85));
+        assert_eq!(parse_review_count("120"), None);
+    }
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("4.8"), Some(4.8));
+        assert_eq!(parse_rating("4,5"), Some(4.5));
+        assert_eq!(parse_rating("6.5"), None);
+        assert_eq!(parse_rating("0"), None);
+        assert_eq!(parse_rating("Baru"), None);
+    }
+
+    #[test]
+    fn test_json_values() {
+        assert_eq!(rating_from_json(&json!(96)), Some(4.8));
+        assert_eq!(rating_from_json(&json!(6)), None);
+        assert_eq!(rating_from_json(&json!("10")), None);
+        assert_eq!(rating_from_json(&json!("4.9")), Some(4.9));
+        assert_eq!(count_from_json(&json!(1500)), Some(1_500));
+        assert_eq!(count_from_json(&json!("10rb+")), Some(10_000));
+    }
+}
@@ -0,0 +1,185 @@
+use serde_json::Value;
+
+/// Numeric price information of a listing, in rupiah
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceInfo {
+    /// Price the product currently sells for
+    pub price_idr: u64,
+    /// Price before discount, only when it is higher than the selling price
+    pub original_price_idr: Option<u64>,
+    /// Discount shown on the listing, or computed from the two prices
+    pub discount_percent: Option<u8>,
+}
+
+impl PriceInfo {
+    /// Combine a selling price with optional original price and discount label
+    ///
+    /// The original price is dropped when it is not above the selling price, and
+    /// a missing discount is computed from the two prices.
+    pub fn new(price_idr: u64, original_price_idr: Option<u64>, discount_percent: Option<u8>) -> Self {
+        let original_price_idr = original_price_idr.filter(|original| *original > price_idr);
+        let discount_percent = discount_percent
+            .filter(|percent| (1..=100).contains(percent))
+            .or_else(|| original_price_idr.map(|original| compute_discount_percent(price_idr, original)));
+
+        Self {
+            price_idr,
+            original_price_idr,
+            discount_percent,
+        }
+    }
+
+    /// Display string of the selling price, e.g. "Rp60.270"
+    pub fn display(&self) -> String {
+        format_rupiah(self.price_idr)
+    }
+}
+
+/// Parse a rupiah amount such as "Rp60.270", "Rp 1.250.000" or "86.100"
+///
+/// Dots and commas are treated as thousand separators, except for a trailing
+/// two-digit decimal part ("Rp15.000,00"). Text after the amount makes the
+/// value invalid, so "Rp500rb/bln" installment labels are rejected. For price
+/// ranges ("Rp10.000 - Rp20.000") the lower bound is returned.
+pub fn parse_rupiah(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let text = text.split(" - ").next().unwrap_or(text).trim();
+    let amount = text
+        .strip_prefix("Rp")
+        .or_else(|| text.strip_prefix("IDR"))
+        .unwrap_or(text)
+        .trim();
+
+    if amount.is_empty() || !amount.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return None;
+    }
+
+    // Drop a ",00" style decimal part before removing separators
+    let amount = match amount.rsplit_once(',') {
+        Some((whole, decimals)) if decimals.len() == 2 => whole,
+        _ => amount,
+    };
+
+    let digits: String = amount.chars().filter(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+/// Parse a discount label such as "30%", "-30 %" or "Diskon 30%"
+pub fn parse_discount_percent(text: &str) -> Option<u8> {
+    let before_percent = text.split('%').next()?;
+    if before_percent.len() == text.len() {
+        return None;
+    }
+
+    let digits: String = before_percent
+        .chars()
+        .rev()
+        .skip_while(|c| c.is_whitespace())
+        .take_while(char::is_ascii_digit)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    digits.parse().ok().filter(|percent| (1..=100).contains(percent))
+}
+
+/// Read a rupiah amount from a JSON value that is either a number or a display string
+pub fn rupiah_from_json(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64().or_else(|| n.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        Value::String(s) => parse_rupiah(s),
+        _ => None,
+    }
+}
+
+/// Read a discount percentage from a JSON value such as `30`, `"30%"` or `"30"`
+pub fn discount_from_json(value: &Value) -> Option<u8> {
+    match value {
+        Value::Number(n) => n.as_f64().filter(|f| *f >= 1.0 && *f <= 100.0).map(|f| f.round() as u8),
+        Value::String(s) => parse_discount_percent(s)
+            .or_else(|| s.trim().parse::<u8>().ok().filter(|p| (1..=100).contains(p))),
+        _ => None,
+    }
+}
+
+/// Format a rupiah amount the Indonesian way, with dots as thousand separators
+pub fn format_rupiah(amount: u64) -> String {
+    let amount_str = amount.to_string();
+    let mut result = String::new();
+    let chars: Vec<char> = amount_str.chars().collect();
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if i > 0 && (chars.len() - i) % 3 == 0 {
+            result.push('.');
+        }
+        result.push(ch);
+    }
+
+    format!("Rp{result}")
+}
+
+fn compute_discount_percent(price_idr: u64, original_price_idr: u64) -> u8 {
+    let saved = original_price_idr.saturating_sub(price_idr) as f64;
+    ((saved / original_price_idr as f64) * 100.0).round().clamp(1.0, 100.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rupiah_formats() {
+        assert_eq!(parse_rupiah("Rp60.270"), Some(60_270));
+        assert_eq!(parse_rupiah(" Rp 1.250.000 "), Some(1_250_000));
+        assert_eq!(parse_rupiah("86.100"), Some(86_100));
+        assert_eq!(parse_rupiah("Rp15.000,00"), Some(15_000));
+        assert_eq!(parse_rupiah("Rp10.000 - Rp20.000"), Some(10_000));
+        assert_eq!(parse_rupiah("IDR 99,000"), Some(99_000));
+    }
+
+    #[test]
+    fn test_parse_rupiah_rejects_non_prices() {
+        assert_eq!(parse_rupiah("Rp500rb/bln"), None);
+        assert_eq!(parse_rupiah("Cashback Rp10.000"), None);
+        assert_eq!(parse_rupiah("Rp"), None);
+    }
+
+    #[test]
+    fn test_parse_discount_percent() {
+        assert_eq!(parse_discount_percent("30% "), Some(30));
+        assert_eq!(parse_discount_percent("Diskon 50 %"), Some(50));
+        assert_eq!(parse_discount_percent("-5%"), Some(5));
+        assert_eq!(parse_discount_percent("30"), None);
+    }
+
+    #[test]
+    fn test_price_info_computes_missing_discount() {
+        let info = PriceInfo::new(60_270, Some(86_100), None);
+        assert_eq!(info.discount_percent, Some(30));
+        assert_eq!(info.display(), "Rp60.270");
+    }
+
+    #[test]
+    fn test_price_info_drops_original_not_above_price() {
+        let info = PriceInfo::new(45_000, Some(45_000), None);
+        assert_eq!(info.original_price_idr, None);
+        assert_eq!(info.discount_percent, None);
+    }
+
+    #[test]
+    fn test_json_values() {
+        assert_eq!(rupiah_from_json(&json!(60270)), Some(60_270));
+        assert_eq!(rupiah_from_json(&json!("Rp60.270")), Some(60_270));
+        assert_eq!(discount_from_json(&json!("30%")), Some(30));
+        assert_eq!(discount_from_json(&json!(12.6)), Some(13));
+    }
+
+    #[test]
+    fn test_format_rupiah() {
+        assert_eq!(format_rupiah(0), "Rp0");
+        assert_eq!(format_rupiah(999), "Rp999");
+        assert_eq!(format_rupiah(19_999_000), "Rp19.999.000");
+    }
+}
@@ -0,0 +1,407 @@
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+use crate::marketplace::marketplace_model::{ProductDetail, ProductVariant};
+use crate::popularity::{count_from_json, parse_count, rating_from_json};
+use crate::pricing::{rupiah_from_json, PriceInfo};
+
+/// Fill a product detail from the schema.org JSON-LD embedded in a product page
+///
+/// Both marketplaces publish a `Product` (or `ProductGroup`) object and a
+/// `BreadcrumbList`. Fields they leave out stay empty so the marketplace can
+/// complete them from the DOM.
+pub fn product_detail_from_json_ld(document: &Html) -> ProductDetail {
+    let mut detail = ProductDetail::default();
+
+    for object in json_ld_objects(document) {
+        if has_type(&object, "Product") || has_type(&object, "ProductGroup") {
+            fill_from_product(&mut detail, &object);
+        } else if has_type(&object, "BreadcrumbList") {
+            detail.categories = breadcrumb_names(&object);
+        }
+    }
+
+    detail
+}
+
+/// Set the display price and drop categories that are just the home page or the product itself
+pub fn finish_product_detail(detail: &mut ProductDetail) {
+    if detail.price_idr == 0 {
+        if let Some(lowest) = detail.variants.iter().filter_map(|v| v.price_idr).min() {
+            detail.price_idr = lowest;
+        }
+    }
+    let price = PriceInfo::new(detail.price_idr, detail.original_price_idr, detail.discount_percent);
+    detail.original_price_idr = price.original_price_idr;
+    detail.discount_percent = price.discount_percent;
+    detail.price = price.display();
+
+    let name = detail.name.to_lowercase();
+    detail.categories.retain(|category| {
+        let lower = category.to_lowercase();
+        !matches!(lower.as_str(), "home" | "beranda") && lower != name
+    });
+
+    let mut seen = std::collections::HashSet::new();
+    detail.images.retain(|image| seen.insert(image.clone()));
+}
+
+/// Parse a stock label such as "Stok: 25", "Sisa 5" or "Stok habis"
+pub fn parse_stock(text: &str) -> Option<u64> {
+    let lower = text.to_lowercase();
+    if !lower.contains("stok") && !lower.contains("stock") && !lower.contains("sisa") {
+        return None;
+    }
+    if lower.contains("habis") || lower.contains("out of stock") {
+        return Some(0);
+    }
+    parse_count(&lower)
+}
+
+/// Parse a weight such as "Berat Satuan: 1.200 g", "1,5 kg" or "500 gram" into grams
+///
+/// Numbers are written the Indonesian way: `.` separates thousands, `,` decimals.
+pub fn parse_weight_grams(text: &str) -> Option<u64> {
+    let lower = text.to_lowercase();
+    let start = lower.find(|c: char| c.is_ascii_digit())?;
+    let rest = &lower[start..];
+    let bytes = rest.as_bytes();
+
+    let mut number = String::new();
+    let mut number_len = 0;
+    while let Some(&byte) = bytes.get(number_len) {
+        let is_thousands_dot = byte == b'.'
+            && bytes.len() >= number_len + 4
+            && bytes[number_len + 1..number_len + 4].iter().all(u8::is_ascii_digit)
+            && !bytes.get(number_len + 4).is_some_and(u8::is_ascii_digit);
+        match byte {
+            b'0'..=b'9' => number.push(byte as char),
+            b',' => number.push('.'),
+            _ if is_thousands_dot => {}
+            _ => break,
+        }
+        number_len += 1;
+    }
+    let value: f64 = number.parse().ok()?;
+    let unit = rest[number_len..].trim_start();
+
+    let grams = if unit.starts_with("kg") || unit.starts_with("kilo") {
+        value * 1_000.0
+    } else if unit.starts_with('g') {
+        value
+    } else {
+        return None;
+    };
+    Some(grams.round() as u64)
+}
+
+/// Trimmed text of the first element matching `selector`, with whitespace collapsed
+pub fn first_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document
+        .select(&selector)
+        .map(|element| collapse_whitespace(&element.text().collect::<Vec<_>>().join(" ")))
+        .find(|text| !text.is_empty())
+}
+
+/// Trimmed texts of every element matching `selector`, skipping empty ones
+pub fn all_texts(document: &Html, selector: &str) -> Vec<String> {
+    let Ok(selector) = Selector::parse(selector) else {
+        return Vec::new();
+    };
+    document
+        .select(&selector)
+        .map(|element| collapse_whitespace(&element.text().collect::<Vec<_>>().join(" ")))
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// Text of the first element matching `selector`, one line per text node
+///
+/// Used for descriptions, where line breaks carry meaning.
+pub fn first_multiline_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document
+        .select(&selector)
+        .map(|element| {
+            element
+                .text()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .find(|text| !text.is_empty())
+}
+
+/// Value of `attr` on the first element matching `selector` that has it
+pub fn first_attr(document: &Html, selector: &str, attr: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    document
+        .select(&selector)
+        .filter_map(|element| element.value().attr(attr))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Absolute image URLs of every `img` matching `selector` (lazy-loaded `data-src` included)
+pub fn image_sources(document: &Html, selector: &str) -> Vec<String> {
+    let Ok(selector) = Selector::parse(selector) else {
+        return Vec::new();
+    };
+    document
+        .select(&selector)
+        .filter_map(|img| img.value().attr("src").or_else(|| img.value().attr("data-src")))
+        .filter(|src| src.starts_with("http"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Every JSON-LD object on the page, with arrays and `@graph` containers flattened
+fn json_ld_objects(document: &Html) -> Vec<Value> {
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    let mut objects = Vec::new();
+
+    for script in document.select(&selector) {
+        let text = script.text().collect::<String>();
+        let Ok(value) = serde_json::from_str::<Value>(text.trim()) else {
+            continue;
+        };
+        let mut pending = vec![value];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Array(items) => pending.extend(items),
+                Value::Object(mut map) => match map.remove("@graph") {
+                    Some(graph) => pending.push(graph),
+                    None => objects.push(Value::Object(map)),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    objects
+}
+
+fn has_type(object: &Value, wanted: &str) -> bool {
+    match object.get("@type") {
+        Some(Value::String(kind)) => kind == wanted,
+        Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind.as_str() == Some(wanted)),
+        _ => false,
+    }
+}
+
+fn json_text(value: Option<&Value>) -> Option<String> {
+    value
+        .and_then(Value::as_str)
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn fill_from_product(detail: &mut ProductDetail, product: &Value) {
+    if let Some(name) = json_text(product.get("name")) {
+        detail.name = name;
+    }
+    if let Some(description) = json_text(product.get("description")) {
+        detail.description = description;
+    }
+    detail.images.extend(json_images(product.get("image")));
+
+    if let Some(rating) = product.get("aggregateRating") {
+        detail.rating = rating.get("ratingValue").and_then(rating_from_json);
+        detail.review_count = rating
+            .get("reviewCount")
+            .or_else(|| rating.get("ratingCount"))
+            .and_then(count_from_json);
+    }
+
+    if let Some(weight) = product.get("weight") {
+        detail.weight_grams = json_weight_grams(weight);
+    }
+
+    let offers = json_offers(product.get("offers"));
+    if let Some(low_price) = product.get("offers").and_then(|o| o.get("lowPrice")).and_then(rupiah_from_json) {
+        detail.price_idr = low_price;
+    } else if let Some(lowest) = offers.iter().filter_map(|o| o.get("price").and_then(rupiah_from_json)).min() {
+        detail.price_idr = lowest;
+    }
+    if offers.len() == 1 {
+        detail.stock = offer_stock(offers[0]);
+    }
+    if offers.len() > 1 {
+        detail.variants = offers.iter().filter_map(|offer| offer_variant(offer, offer)).collect();
+    }
+
+    // schema.org ProductGroup lists its variants as separate products
+    if let Some(Value::Array(variants)) = product.get("hasVariant") {
+        detail.variants = variants
+            .iter()
+            .filter_map(|variant| {
+                let offers = json_offers(variant.get("offers"));
+                offer_variant(variant, offers.first().copied().unwrap_or(variant))
+            })
+            .collect();
+    }
+}
+
+fn json_images(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(url)) => vec![url.clone()],
+        Some(Value::Array(items)) => items.iter().flat_map(|item| json_images(Some(item))).collect(),
+        Some(Value::Object(image)) => json_text(image.get("url").or_else(|| image.get("contentUrl")))
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Individual offers, whether listed directly or inside an `AggregateOffer`
+fn json_offers(value: Option<&Value>) -> Vec<&Value> {
+    match value {
+        Some(Value::Array(offers)) => offers.iter().collect(),
+        Some(offer @ Value::Object(map)) => match map.get("offers") {
+            Some(Value::Array(offers)) => offers.iter().collect(),
+            _ if map.contains_key("price") => vec![offer],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn offer_stock(offer: &Value) -> Option<u64> {
+    let level = offer.get("inventoryLevel")?;
+    count_from_json(level.get("value").unwrap_or(level))
+}
+
+fn offer_variant(named: &Value, offer: &Value) -> Option<ProductVariant> {
+    let name = json_text(named.get("name"))
+        .or_else(|| json_text(offer.get("itemOffered").and_then(|item| item.get("name"))))
+        .or_else(|| json_text(named.get("sku")))?;
+    Some(ProductVariant {
+        name,
+        price_idr: offer.get("price").and_then(rupiah_from_json),
+        stock: offer_stock(offer),
+        available: offer
+            .get("availability")
+            .and_then(Value::as_str)
+            .map(|availability| availability.ends_with("InStock")),
+    })
+}
+
+fn json_weight_grams(weight: &Value) -> Option<u64> {
+    match weight {
+        Value::String(text) => parse_weight_grams(text),
+        Value::Object(map) => {
+            let value = map.get("value")?;
+            let value = value.as_f64().or_else(|| value.as_str()?.replace(',', ".").parse().ok())?;
+            let unit = map.get("unitCode").or_else(|| map.get("unitText")).and_then(Value::as_str).unwrap_or("g");
+            let grams = match unit.to_lowercase().as_str() {
+                "kgm" | "kg" => value * 1_000.0,
+                _ => value,
+            };
+            Some(grams.round() as u64)
+        }
+        _ => None,
+    }
+}
+
+fn breadcrumb_names(list: &Value) -> Vec<String> {
+    let Some(Value::Array(items)) = list.get("itemListElement") else {
+        return Vec::new();
+    };
+    let mut items: Vec<&Value> = items.iter().collect();
+    items.sort_by_key(|item| item.get("position").and_then(Value::as_u64).unwrap_or(u64::MAX));
+    items
+        .into_iter()
+        .filter_map(|item| json_text(item.get("name")).or_else(|| json_text(item.get("item").and_then(|i| i.get("name")))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCT_PAGE: &str = r#"
+        <html><head>
+        <script type="application/ld+json">
+        {
+            "@context": "https://schema.org",
+            "@graph": [
+                {
+                    "@type": "Product",
+                    "name": "Kaos Polos Cotton Combed 30s",
+                    "description": "Bahan adem.\nTersedia banyak warna.",
+                    "image": ["https://images.example/1.jpg", {"@type": "ImageObject", "url": "https://images.example/2.jpg"}],
+                    "weight": {"@type": "QuantitativeValue", "value": 0.2, "unitCode": "KGM"},
+                    "aggregateRating": {"@type": "AggregateRating", "ratingValue": "4.9", "reviewCount": "1,2rb"},
+                    "offers": {
+                        "@type": "AggregateOffer",
+                        "lowPrice": 35000,
+                        "highPrice": 45000,
+                        "offers": [
+                            {"@type": "Offer", "name": "Hitam, M", "price": "35000", "availability": "https://schema.org/InStock", "inventoryLevel": {"value": 12}},
+                            {"@type": "Offer", "name": "Putih, XL", "price": 45000, "availability": "https://schema.org/OutOfStock"}
+                        ]
+                    }
+                },
+                {
+                    "@type": "BreadcrumbList",
+                    "itemListElement": [
+                        {"@type": "ListItem", "position": 2, "name": "Fashion Pria"},
+                        {"@type": "ListItem", "position": 1, "name": "Beranda"},
+                        {"@type": "ListItem", "position": 3, "item": {"name": "Kaos"}}
+                    ]
+                }
+            ]
+        }
+        </script>
+        </head><body></body></html>
+    "#;
+
+    #[test]
+    fn test_product_detail_from_json_ld() {
+        let mut detail = product_detail_from_json_ld(&Html::parse_document(PRODUCT_PAGE));
+        finish_product_detail(&mut detail);
+
+        assert_eq!(detail.name, "Kaos Polos Cotton Combed 30s");
+        assert_eq!(detail.description, "Bahan adem.\nTersedia banyak warna.");
+        assert_eq!(detail.images, vec!["https://images.example/1.jpg", "https://images.example/2.jpg"]);
+        assert_eq!(detail.price_idr, 35_000);
+        assert_eq!(detail.price, "Rp35.000");
+        assert_eq!(detail.weight_grams, Some(200));
+        assert_eq!(detail.rating, Some(4.9));
+        assert_eq!(detail.review_count, Some(1_200));
+        assert_eq!(detail.categories, vec!["Fashion Pria", "Kaos"]);
+        assert_eq!(
+            detail.variants,
+            vec![
+                ProductVariant { name: "Hitam, M".into(), price_idr: Some(35_000), stock: Some(12), available: Some(true) },
+                ProductVariant { name: "Putih, XL".into(), price_idr: Some(45_000), stock: None, available: Some(false) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stock() {
+        assert_eq!(parse_stock("Stok: 25"), Some(25));
+        assert_eq!(parse_stock("Stok Total: Sisa 5"), Some(5));
+        assert_eq!(parse_stock("Stok habis"), Some(0));
+        assert_eq!(parse_stock("Terjual 100+"), None);
+    }
+
+    #[test]
+    fn test_parse_weight_grams() {
+        assert_eq!(parse_weight_grams("Berat Satuan: 200 g"), Some(200));
+        assert_eq!(parse_weight_grams("1,5 kg"), Some(1_500));
+        assert_eq!(parse_weight_grams("500 gram"), Some(500));
+        assert_eq!(parse_weight_grams("1.200 g"), Some(1_200));
+        assert_eq!(parse_weight_grams("1.250 gram"), Some(1_250));
+        assert_eq!(parse_weight_grams("1.250,5 kg"), Some(1_250_500));
+        assert_eq!(parse_weight_grams("20 x 10 cm"), None);
+    }
+}
@@ -0,0 +1,17 @@
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use tracing::info;
+
+/// Connect to the Redis server at `url`, see `redis.url`
+///
+/// The returned manager multiplexes one connection and reconnects on its own
+/// when Redis restarts; clones share it, so the cache and the job store use
+/// the same connection.
+pub async fn connect(url: &str) -> Result<ConnectionManager> {
+    let client = redis::Client::open(url)?;
+    let connection = ConnectionManager::new(client)
+        .await
+        .context("Failed to connect to Redis")?;
+    info!("Connected to Redis");
+    Ok(connection)
+}
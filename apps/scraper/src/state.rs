@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use crate::cache::{Cache, CacheStats, RefreshTracker};
+use crate::job::{JobQueue, JobStore, MemoryJobStore};
+use crate::marketplace::marketplace_registry::MarketplaceRegistry;
+use crate::page_source::PageSources;
+use crate::watch::{SqliteWatchStore, WatchStore};
+
+/// Shared application state handed to every axum handler
+#[derive(Clone)]
+pub struct AppState {
+    pub page_sources: PageSources,
+    pub marketplaces: Arc<MarketplaceRegistry>,
+    pub cache: Arc<dyn Cache>,
+    pub cache_stats: Arc<CacheStats>,
+    /// Cache entries being scraped again in the background
+    pub refreshes: Arc<RefreshTracker>,
+    /// Asynchronous scrape jobs
+    pub jobs: Arc<JobQueue>,
+    /// Watchlists and price history
+    pub watches: Arc<dyn WatchStore>,
+}
+
+impl AppState {
+    pub fn new(page_sources: PageSources, marketplaces: MarketplaceRegistry, cache: Arc<dyn Cache>) -> Self {
+        Self {
+            page_sources,
+            marketplaces: Arc::new(marketplaces),
+            cache,
+            cache_stats: Arc::default(),
+            refreshes: Arc::default(),
+            jobs: Arc::new(JobQueue::new(Arc::new(MemoryJobStore::default()))),
+            watches: Arc::new(SqliteWatchStore::open_in_memory().expect("Failed to create in-memory SQLite database")),
+        }
+    }
+
+    /// Keep jobs in `store` instead of in memory
+    pub fn with_job_store(mut self, store: Arc<dyn JobStore>) -> Self {
+        self.jobs = Arc::new(JobQueue::new(store));
+        self
+    }
+
+    /// Keep watches and price history in `store` instead of a temporary database
+    pub fn with_watch_store(mut self, store: Arc<dyn WatchStore>) -> Self {
+        self.watches = store;
+        self
+    }
+}
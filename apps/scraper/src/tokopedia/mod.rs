@@ -1,13 +1,5 @@
-pub mod tokopedia_controller;
-pub mod tokopedia_dto;
-pub mod tokopedia_model;
-pub mod tokopedia_repository;
-pub mod tokopedia_service;
+pub mod tokopedia_marketplace;
+pub mod tokopedia_parser;
 
-use axum::{Router, routing::get};
-
-/// Create router for Tokopedia scraper endpoints
-pub fn router() -> Router {
-    Router::new()
-        .route("/api/scraper/tokopedia", get(tokopedia_controller::tokopedia_handler))
-}
+pub use tokopedia_marketplace::TokopediaMarketplace;
+pub use tokopedia_parser::TokopediaParser;
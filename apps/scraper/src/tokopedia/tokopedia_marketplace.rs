@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+
+use crate::block_detection::PageSignatures;
+use crate::config::*;
+use crate::marketplace::marketplace_model::{
+    Product, ProductCondition, ProductDetail, SearchFilters, SortOrder,
+};
+use crate::marketplace::Marketplace;
+use crate::page_source::RenderOptions;
+use crate::tokopedia::TokopediaParser;
+
+/// Script that counts the valid product cards currently rendered on a search page
+const PRODUCT_COUNT_SCRIPT: &str = r#"
+    (function() {
+        const container = document.querySelector('div[data-testid="divSRPContentProducts"]');
+        if (!container) return 0;
+
+        const productLinks = container.querySelectorAll('a[href*="tokopedia.com"]');
+        let validProducts = 0;
+        const seenUrls = new Set();
+
+        for (const link of productLinks) {
+            const href = link.getAttribute('href') || '';
+
+            // Make full URL if relative
+            let fullUrl = href;
+            if (!href.startsWith('http')) {
+                if (href.startsWith('/')) {
+                    fullUrl = 'https://www.tokopedia.com' + href;
+                }
+            }
+
+            // Basic validation - exclude search/discovery/promo pages
+            if (!fullUrl ||
+                fullUrl.includes('/search') ||
+                fullUrl.includes('/discovery/') ||
+                fullUrl.includes('/top-ads/') ||
+                fullUrl.includes('/promo/') ||
+                seenUrls.has(fullUrl)) {
+                continue;
+            }
+
+            seenUrls.add(fullUrl);
+
+            // Simplified check: just verify link has some text content
+            const textContent = link.textContent.trim();
+            if (textContent.length > 10) {
+                validProducts++;
+            }
+        }
+
+        return validProducts;
+    })();
+"#;
+
+/// Script that reports whether a product page has rendered its title
+const PRODUCT_PAGE_READY_SCRIPT: &str = r#"
+    (function() {
+        return document.querySelector('h1[data-testid="lblPDPDetailProductName"]') ? 1 : 0;
+    })();
+"#;
+
+/// Tokopedia's own empty search and error pages
+const PAGE_SIGNATURES: PageSignatures = PageSignatures {
+    empty: &["produk nggak ditemukan", "data-testid=\"imgsrpemptystate\""],
+    error: &["yah, ada gangguan", "terjadi kesalahan"],
+    // The embedded search payload, or the results container rendered without it
+    search_page: &["\"searchproduct\"", "data-testid=\"divsrpcontentproducts\""],
+    ..PageSignatures::NONE
+};
+
+pub struct TokopediaMarketplace;
+
+#[async_trait]
+impl Marketplace for TokopediaMarketplace {
+    fn id(&self) -> &'static str {
+        "tokopedia"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Tokopedia"
+    }
+
+    fn domain(&self) -> &'static str {
+        "tokopedia.com"
+    }
+
+    /// Build Tokopedia search URL with query parameters
+    fn build_search_url(&self, query: &str, filters: &SearchFilters, page: u32) -> String {
+        let mut url = format!(
+            "{}{}?st=product&q={}",
+            TOKOPEDIA_BASE_URL,
+            SEARCH_ENDPOINT,
+            urlencoding::encode(query)
+        );
+        if let Some(min) = filters.min_price {
+            url.push_str(&format!("&pmin={min}"));
+        }
+        if let Some(max) = filters.max_price {
+            url.push_str(&format!("&pmax={max}"));
+        }
+        // Tokopedia's "ob" (order by) codes
+        let order_by = match filters.sort {
+            SortOrder::Relevance => None,
+            SortOrder::LowestPrice => Some(3),
+            SortOrder::HighestPrice => Some(4),
+            SortOrder::BestSelling => Some(8),
+            SortOrder::Newest => Some(9),
+        };
+        if let Some(order_by) = order_by {
+            url.push_str(&format!("&ob={order_by}"));
+        }
+        if filters.official_store_only {
+            url.push_str("&official=true");
+        }
+        match filters.condition {
+            Some(ProductCondition::New) => url.push_str("&condition=1"),
+            Some(ProductCondition::Used) => url.push_str("&condition=2"),
+            None => {}
+        }
+        // Tokopedia filters on whole stars, listed as e.g. "4,5" for 4 stars and up
+        if let Some(rating) = filters.rating_min.filter(|r| *r >= 1.0) {
+            let stars: Vec<String> = (rating.floor() as u8..=5).map(|star| star.to_string()).collect();
+            url.push_str(&format!("&rt={}", urlencoding::encode(&stars.join(","))));
+        }
+        if page > 1 {
+            url.push_str(&format!("&page={page}"));
+        }
+        url
+    }
+
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
+            marketplace: self.id(),
+            ready_selector: r#"div[data-testid="divSRPContentProducts"] a[href*="tokopedia.com"]"#,
+            product_count_script: PRODUCT_COUNT_SCRIPT,
+            timing: settings().render_timing(self.id()),
+        }
+    }
+
+    fn page_signatures(&self) -> &'static PageSignatures {
+        &PAGE_SIGNATURES
+    }
+
+    fn parse(&self, html: &str, limit: usize) -> Vec<Product> {
+        TokopediaParser.parse_search_page(html, limit)
+    }
+
+    fn supports_http_mode(&self) -> bool {
+        true
+    }
+
+    /// Search results ship in the `__NEXT_DATA__` JSON of the server-rendered page
+    fn parse_embedded(&self, html: &str, limit: usize) -> Option<Vec<Product>> {
+        TokopediaParser.parse_products_from_json(html, limit)
+    }
+
+    fn product_render_options(&self) -> RenderOptions {
+        RenderOptions {
+            marketplace: self.id(),
+            ready_selector: r#"h1[data-testid="lblPDPDetailProductName"]"#,
+            product_count_script: PRODUCT_PAGE_READY_SCRIPT,
+            timing: settings().render_timing(self.id()),
+        }
+    }
+
+    fn parse_product_detail(&self, html: &str, url: &str) -> Option<ProductDetail> {
+        TokopediaParser.parse_product_page(html, url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_search_url_without_filters() {
+        let url = TokopediaMarketplace.build_search_url("iphone 15", &SearchFilters::default(), 1);
+        assert_eq!(url, "https://www.tokopedia.com/search?st=product&q=iphone%2015");
+    }
+
+    #[test]
+    fn test_build_search_url_with_filters() {
+        let filters = SearchFilters {
+            min_price: Some(1_000_000),
+            max_price: Some(5_000_000),
+            sort: SortOrder::LowestPrice,
+            official_store_only: true,
+            condition: Some(ProductCondition::New),
+            rating_min: Some(4.5),
+        };
+        let url = TokopediaMarketplace.build_search_url("laptop", &filters, 2);
+        assert_eq!(
+            url,
+            "https://www.tokopedia.com/search?st=product&q=laptop&pmin=1000000&pmax=5000000&ob=3&official=true&condition=1&rt=4%2C5&page=2"
+        );
+    }
+}
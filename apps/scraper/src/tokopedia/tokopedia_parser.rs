@@ -0,0 +1,588 @@
+use scraper::{Html, Selector};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::config::settings;
+use crate::marketplace::marketplace_model::{Product, ProductDetail, ProductVariant};
+use crate::metrics::metrics;
+use crate::popularity::{
+    count_from_json, parse_rating, parse_review_count, parse_sold_count, rating_from_json,
+};
+use crate::pricing::{discount_from_json, parse_discount_percent, parse_rupiah, rupiah_from_json, PriceInfo};
+use crate::product_detail::{
+    all_texts, finish_product_detail, first_multiline_text, first_text, image_sources, parse_stock,
+    parse_weight_grams, product_detail_from_json_ld,
+};
+
+/// Marketplace id stored in every parsed product
+const SOURCE: &str = "tokopedia";
+
+/// Safely truncate a string to a maximum number of characters (not bytes)
+/// This respects Unicode character boundaries to avoid panics
+fn truncate_str(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        None => s,
+        Some((idx, _)) => &s[..idx],
+    }
+}
+
+/// Turns Tokopedia HTML into products
+///
+/// Works on HTML alone, so it can be used on stored pages without a browser.
+pub struct TokopediaParser;
+
+impl TokopediaParser {
+    /// Parse a search results page, preferring the embedded `__NEXT_DATA__` JSON over the DOM
+    pub fn parse_search_page(&self, html: &str, limit: usize) -> Vec<Product> {
+        // Debug: Check if __NEXT_DATA__ exists
+        if html.contains("__NEXT_DATA__") {
+            debug!("Found __NEXT_DATA__ in HTML");
+        } else {
+            debug!("No __NEXT_DATA__ found in HTML");
+        }
+
+        // Try to parse from __NEXT_DATA__ JSON first (faster and more reliable)
+        self.parse_products_from_json(html, limit).unwrap_or_else(|| {
+            warn!("JSON parsing failed, falling back to DOM parsing...");
+            metrics().parse_fallbacks.with_label_values(&[SOURCE]).inc();
+            self.parse_products_from_dom(html, limit)
+        })
+    }
+
+    /// Parse a product page: JSON-LD first, completed and overridden by the visible DOM
+    pub fn parse_product_page(&self, html: &str, url: &str) -> Option<ProductDetail> {
+        let document = Html::parse_document(html);
+        let mut detail = product_detail_from_json_ld(&document);
+
+        if let Some(name) = first_text(&document, r#"h1[data-testid="lblPDPDetailProductName"]"#) {
+            detail.name = name;
+        }
+        if detail.name.is_empty() {
+            return None;
+        }
+
+        if let Some(price) = first_text(&document, r#"div[data-testid="lblPDPDetailProductPrice"]"#).and_then(|t| parse_rupiah(&t)) {
+            detail.price_idr = price;
+        }
+        detail.original_price_idr = first_text(&document, r#"[data-testid="lblPDPDetailOriginalPrice"]"#)
+            .and_then(|t| parse_rupiah(&t))
+            .or(detail.original_price_idr);
+        detail.discount_percent = first_text(&document, r#"[data-testid="lblPDPDetailDiscountPercentage"]"#)
+            .and_then(|t| parse_discount_percent(&t))
+            .or(detail.discount_percent);
+
+        if let Some(description) = first_multiline_text(&document, r#"div[data-testid="lblPDPDescriptionProduk"]"#) {
+            detail.description = description;
+        }
+        detail.images.extend(image_sources(
+            &document,
+            r#"img[data-testid="PDPMainImage"], button[data-testid="PDPImageThumbnail"] img"#,
+        ));
+
+        // "Kondisi: Baru", "Berat Satuan: 200 g", "Etalase: ..."
+        for info in all_texts(&document, r#"ul[data-testid="lblPDPInfoProduk"] li"#) {
+            if info.to_lowercase().contains("berat") {
+                detail.weight_grams = parse_weight_grams(&info).or(detail.weight_grams);
+            }
+        }
+        detail.stock = first_text(&document, r#"[data-testid="stock-label"]"#)
+            .and_then(|t| parse_stock(&t))
+            .or(detail.stock);
+
+        // The DOM only shows variant names; prices come from JSON-LD when available
+        if detail.variants.is_empty() {
+            detail.variants = all_texts(&document, r#"div[data-testid="pdpVariantContainer"] button"#)
+                .into_iter()
+                .map(|name| ProductVariant { name, ..ProductVariant::default() })
+                .collect();
+        }
+
+        detail.shop_name = first_text(&document, r#"a[data-testid="llbPDPFooterShopName"]"#);
+        detail.shop_badge = self.shop_badge(&document);
+        if detail.categories.is_empty() {
+            detail.categories = all_texts(&document, r#"ol[data-testid="lnkPDPBreadcrumb"] li, nav[aria-label="breadcrumb"] li"#);
+        }
+
+        detail.rating = first_text(&document, r#"[data-testid="lblPDPDetailProductRatingNumber"]"#)
+            .and_then(|t| parse_rating(&t))
+            .or(detail.rating);
+        detail.review_count = first_text(&document, r#"[data-testid="lblPDPDetailProductRatingCounter"]"#)
+            .and_then(|t| parse_review_count(&t))
+            .or(detail.review_count);
+        detail.sold_min = first_text(&document, r#"[data-testid="lblPDPDetailProductSoldCounter"]"#)
+            .and_then(|t| parse_sold_count(&t));
+
+        detail.product_url = url.to_string();
+        detail.source = SOURCE.to_string();
+        finish_product_detail(&mut detail);
+        Some(detail)
+    }
+
+    /// Shop badge shown next to the shop name, e.g. "Official Store"
+    fn shop_badge(&self, document: &Html) -> Option<String> {
+        const BADGES: &[(&str, &str)] = &[
+            (r#"img[data-testid="pdpShopBadgeOS"]"#, "Official Store"),
+            (r#"img[data-testid="pdpShopBadgePMPro"]"#, "Power Merchant Pro"),
+            (r#"img[data-testid="pdpShopBadgePM"]"#, "Power Merchant"),
+        ];
+        BADGES.iter().find_map(|(selector, label)| {
+            let selector = Selector::parse(selector).unwrap();
+            document.select(&selector).next().map(|badge| {
+                badge
+                    .value()
+                    .attr("alt")
+                    .map(str::trim)
+                    .filter(|alt| !alt.is_empty())
+                    .unwrap_or(label)
+                    .to_string()
+            })
+        })
+    }
+
+    /// Parse products from HTML using DOM selectors
+    pub fn parse_products_from_dom(&self, html: &str, limit: usize) -> Vec<Product> {
+        let document = Html::parse_document(html);
+        
+        // Strategy 1: Use data-testid attribute for container (more stable)
+        let container_selector = Selector::parse(r#"div[data-testid="divSRPContentProducts"]"#).unwrap();
+        let link_selector = Selector::parse("a[href*='tokopedia.com']").unwrap();
+        let img_selector = Selector::parse("img[alt='product-image']").unwrap();
+        let span_selector = Selector::parse("span").unwrap();
+
+        let mut products = Vec::new();
+        let mut seen_urls = std::collections::HashSet::new();
+
+        debug!("Searching for products with stable selectors...");
+
+        // Try to find the product container first
+        let product_links: Vec<_> = if let Some(container) = document.select(&container_selector).next() {
+            debug!("Found product container with data-testid");
+            container.select(&link_selector).collect()
+        } else {
+            debug!("Container not found, searching entire document");
+            document.select(&link_selector).collect()
+        };
+
+        debug!("Found {} potential product links", product_links.len());
+
+        for link_elem in product_links {
+            // Extract and validate product URL
+            let product_url = link_elem.value().attr("href").unwrap_or("").to_string();
+            
+            // Make full URL if relative
+            let full_url = if product_url.starts_with("http") {
+                product_url.clone()
+            } else if product_url.starts_with('/') {
+                format!("https://www.tokopedia.com{product_url}")
+            } else {
+                product_url.clone()
+            };
+            
+            // Validate URL - must be a product page, not search or discovery
+            if full_url.is_empty() 
+                || full_url.contains("/search")
+                || full_url.contains("/discovery/") 
+                || full_url.contains("/top-ads/")
+                || full_url.contains("/promo/")
+                || seen_urls.contains(&full_url) {
+                continue;
+            }
+
+            seen_urls.insert(full_url.clone());
+
+            // Extract product name - find longest span text (product names are usually long)
+            let name = link_elem
+                .select(&span_selector)
+                .map(|span| span.text().collect::<String>().trim().to_string())
+                .filter(|text| {
+                    text.len() > 15 // Product names are usually longer than 15 chars
+                    && !text.starts_with("Rp") // Not a price
+                    && !text.contains("terjual") // Not sold count
+                    && !text.chars().all(|c| c.is_numeric() || c == '.' || c == ',') // Not just numbers
+                })
+                .max_by_key(|text| text.len()) // Take the longest text (likely product name)
+                .unwrap_or_default();
+
+            // Extract price - collect ALL text with "Rp" and debug
+            let all_price_candidates: Vec<String> = link_elem
+                .descendants()
+                .filter_map(|node| {
+                    node.value().as_text().map(|t| t.trim().to_string())
+                })
+                .filter(|text| {
+                    text.starts_with("Rp") 
+                    && !text.contains("Cashback")
+                    && !text.contains('%')
+                })
+                .collect();
+            
+            // Debug: show all candidates
+            if !all_price_candidates.is_empty() && all_price_candidates.len() <= 5 {
+                debug!("Price candidates: {all_price_candidates:?}");
+            }
+
+            // The lowest amount is the selling price, a higher one is the strikethrough original price
+            let amounts: Vec<u64> = all_price_candidates
+                .iter()
+                .filter_map(|text| parse_rupiah(text))
+                .collect();
+            let price_info = amounts.iter().min().map(|&price_idr| {
+                // Discount badges are short texts such as "30%"
+                let discount_percent = link_elem
+                    .descendants()
+                    .filter_map(|node| node.value().as_text().map(|t| t.trim().to_string()))
+                    .filter(|text| text.len() <= 5)
+                    .find_map(|text| parse_discount_percent(&text));
+                PriceInfo::new(price_idr, amounts.iter().max().copied(), discount_percent)
+            });
+            let price = price_info.map(|info| info.display()).unwrap_or_default();
+
+            // Debug output
+            if !name.is_empty() || !price.is_empty() {
+                debug!(
+                    "Card: name='{}', price='{}', url='{}'",
+                    if name.is_empty() { "EMPTY" } else { truncate_str(&name, 30) },
+                    if price.is_empty() { "EMPTY" } else { &price },
+                    truncate_str(&full_url, 50)
+                );
+            }
+
+            // Only proceed if we have valid name and price
+            let Some(price_info) = price_info.filter(|_| !name.is_empty()) else {
+                continue;
+            };
+
+            // Extract rating - look for numeric pattern like "4.8" or "5.0"
+            let rating = link_elem
+                .select(&span_selector)
+                .filter_map(|span| {
+                    let text = span.text().collect::<String>().trim().to_string();
+                    // Rating pattern: single digit, dot, single digit (e.g., "4.8")
+                    if text.len() >= 3 && text.len() <= 4 
+                        && text.contains('.') 
+                        && text.chars().filter(|c| c.is_numeric()).count() == 2 {
+                        parse_rating(&text)
+                    } else {
+                        None
+                    }
+                })
+                .next();
+
+            // Extract review count - shown as "(120)" next to the rating on some cards
+            let review_count = link_elem
+                .select(&span_selector)
+                .find_map(|span| parse_review_count(&span.text().collect::<String>()));
+
+            // Extract image URL using stable alt attribute
+            let image_url = link_elem
+                .select(&img_selector)
+                .next()
+                .and_then(|img| {
+                    img.value()
+                        .attr("src")
+                        .or_else(|| img.value().attr("data-src"))
+                })
+                .unwrap_or("")
+                .to_string();
+
+            // Extract shop location - find span with city names
+            let shop_location = link_elem
+                .select(&span_selector)
+                .map(|span| span.text().collect::<String>().trim().to_string())
+                .filter(|text| {
+                    // Match common Indonesian city/region names
+                    settings().parsing.indonesian_cities.iter().any(|city| text.contains(city.as_str()))
+                })
+                .last(); // Usually the last matching span is the location
+
+            // Extract sold count - look for text containing "terjual"
+            let sold = link_elem
+                .select(&span_selector)
+                .map(|span| span.text().collect::<String>().trim().to_string())
+                .find(|text| {
+                    text.to_lowercase().contains("terjual") ||
+                    text.to_lowercase().contains("rb terjual")
+                });
+
+            let sold_min = sold.as_deref().and_then(parse_sold_count);
+
+            products.push(Product {
+                name,
+                price,
+                price_idr: price_info.price_idr,
+                original_price_idr: price_info.original_price_idr,
+                discount_percent: price_info.discount_percent,
+                rating,
+                review_count,
+                image_url,
+                product_url: full_url.clone(),
+                shop_location,
+                sold,
+                sold_min,
+                source: SOURCE.to_string(),
+            });
+            
+            debug!("Found: {} - {}", products.last().unwrap().name, products.last().unwrap().price);
+
+            if products.len() >= limit {
+                break;
+            }
+        }
+
+        info!("DOM parsing extracted {} products", products.len());
+        metrics().parses.with_label_values(&[SOURCE, "dom"]).inc();
+        products
+    }
+
+    /// Parse products from __NEXT_DATA__ JSON
+    pub fn parse_products_from_json(&self, html: &str, limit: usize) -> Option<Vec<Product>> {
+        let after_marker = html.split("__NEXT_DATA__").nth(1)?;
+        // Skip the rest of the opening tag, e.g. `" type="application/json">`
+        let json_str = after_marker[after_marker.find('>')? + 1..]
+            .split("</script>")
+            .next()?
+            .trim();
+
+        let next_data: Value = serde_json::from_str(json_str).ok()?;
+        self.extract_products_from_json_value(&next_data, limit)
+    }
+
+    /// Recursively extract products from JSON structure
+    fn extract_products_from_json_value(&self, value: &Value, limit: usize) -> Option<Vec<Product>> {
+        // Look for product data in various possible locations
+        let mut products = Vec::new();
+
+        // Strategy 1: Find arrays with product objects, stopping at `limit`
+        fn find_products(val: &Value, products: &mut Vec<Product>, limit: usize) {
+            if products.len() >= limit {
+                return;
+            }
+            match val {
+                Value::Array(arr) => {
+                    // Check if this array contains product objects
+                    for item in arr {
+                        if let Some(obj) = item.as_object() {
+                            // Try to parse as product
+                            if let Some(product) = parse_product_from_json(obj) {
+                                products.push(product);
+                                if products.len() >= limit {
+                                    return;
+                                }
+                                continue;
+                            }
+                        }
+                        
+                        // Recurse into nested structures
+                        find_products(item, products, limit);
+                    }
+                }
+                Value::Object(obj) => {
+                    // Try direct product parse
+                    if let Some(product) = parse_product_from_json(obj) {
+                        products.push(product);
+                        return;
+                    }
+                    
+                    // Recurse into object values
+                    for value in obj.values() {
+                        find_products(value, products, limit);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fn parse_product_from_json(obj: &serde_json::Map<String, Value>) -> Option<Product> {
+            // Must have at least name and price-like field
+            let name = obj.get("name")
+                .or_else(|| obj.get("title"))
+                .or_else(|| obj.get("product_name"))?;
+            
+            let name_str = name.as_str()?.to_string();
+            if name_str.len() < 3 {
+                return None;
+            }
+
+            // Try various price field names; newer payloads nest them in a `price` object
+            let price_obj = obj.get("price").and_then(|v| v.as_object());
+            let price = obj.get("price")
+                .filter(|v| !v.is_object())
+                .or_else(|| price_obj.and_then(|p| p.get("text")))
+                .or_else(|| obj.get("priceInt"))
+                .or_else(|| obj.get("product_price"))?;
+
+            let price_idr = rupiah_from_json(price)
+                .or_else(|| obj.get("priceInt").and_then(rupiah_from_json))
+                .or_else(|| price_obj.and_then(|p| p.get("number")).and_then(rupiah_from_json))?;
+
+            let original_price_idr = obj.get("originalPrice")
+                .or_else(|| obj.get("slashedPrice"))
+                .or_else(|| obj.get("original_price"))
+                .or_else(|| price_obj.and_then(|p| p.get("original")))
+                .and_then(rupiah_from_json);
+
+            let discount_percent = obj.get("discountPercentage")
+                .or_else(|| obj.get("discount_percentage"))
+                .or_else(|| price_obj.and_then(|p| p.get("discountPercentage")))
+                .and_then(discount_from_json);
+
+            let price_info = PriceInfo::new(price_idr, original_price_idr, discount_percent);
+            let price_str = price
+                .as_str()
+                .map(|p| p.trim().to_string())
+                .unwrap_or_else(|| price_info.display());
+
+            // Extract other fields; `rating` may be a plain value or an object with average and count
+            let rating_obj = obj.get("rating").and_then(|v| v.as_object());
+            let rating = obj.get("ratingAverage")
+                .or_else(|| obj.get("rating").filter(|v| !v.is_object()))
+                .or_else(|| obj.get("ratingScore"))
+                .or_else(|| rating_obj.and_then(|r| r.get("average")))
+                .and_then(rating_from_json);
+
+            let review_count = obj.get("countReview")
+                .or_else(|| obj.get("reviewCount"))
+                .or_else(|| obj.get("review_count"))
+                .or_else(|| rating_obj.and_then(|r| r.get("count")))
+                .and_then(count_from_json);
+
+            let image_url = obj.get("imageUrl")
+                .or_else(|| obj.get("image"))
+                .or_else(|| obj.get("imageURL"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            let product_url = obj.get("url")
+                .or_else(|| obj.get("link"))
+                .or_else(|| obj.get("productUrl"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            let shop_location = obj.get("shop")
+                .and_then(|s| s.get("location"))
+                .or_else(|| obj.get("shopLocation"))
+                .or_else(|| obj.get("location"))
+                .and_then(|l| l.as_str())
+                .map(String::from);
+
+            let sold_value = obj.get("sold")
+                .or_else(|| obj.get("soldCount"))
+                .or_else(|| obj.get("totalSold"));
+
+            // Search payloads also carry the sold label as a card badge, e.g. "10rb+ terjual"
+            let sold_label = obj.get("labelGroups")
+                .and_then(|v| v.as_array())
+                .and_then(|labels| {
+                    labels.iter()
+                        .filter_map(|label| label.get("title").and_then(|t| t.as_str()))
+                        .find(|title| title.to_lowercase().contains("terjual"))
+                });
+
+            let sold = sold_value
+                .and_then(|v| {
+                    if let Some(s) = v.as_str() {
+                        Some(s.to_string())
+                    } else { v.as_i64().map(|n| format!("{n}")) }
+                })
+                .or_else(|| sold_label.map(String::from));
+
+            let sold_min = sold_value
+                .and_then(count_from_json)
+                .or_else(|| sold_label.and_then(parse_sold_count));
+
+            Some(Product {
+                name: name_str,
+                price: price_str,
+                price_idr: price_info.price_idr,
+                original_price_idr: price_info.original_price_idr,
+                discount_percent: price_info.discount_percent,
+                rating,
+                review_count,
+                image_url,
+                product_url,
+                shop_location,
+                sold,
+                sold_min,
+                source: SOURCE.to_string(),
+            })
+        }
+
+        find_products(value, &mut products, limit);
+
+        if products.is_empty() {
+            None
+        } else {
+            info!("JSON parsing extracted {} products", products.len());
+            metrics().parses.with_label_values(&[SOURCE, "json"]).inc();
+            Some(products)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_products_from_json_stops_at_limit() {
+        let html = include_str!("../../fixtures/www.tokopedia.com_search_st_product_q_iphone_2015.html");
+        assert_eq!(TokopediaParser.parse_products_from_json(html, usize::MAX).unwrap().len(), 3);
+        assert_eq!(TokopediaParser.parse_products_from_json(html, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_product_page_from_dom() {
+        let html = r#"
+            <nav aria-label="breadcrumb"><ol>
+                <li><a href="/">Beranda</a></li>
+                <li><a href="/p/handphone-tablet">Handphone &amp; Tablet</a></li>
+                <li><a href="/p/handphone-tablet/handphone">Handphone</a></li>
+                <li>Samsung Galaxy A15 8/256GB</li>
+            </ol></nav>
+            <img data-testid="PDPMainImage" src="https://images.tokopedia.net/main.jpg">
+            <button data-testid="PDPImageThumbnail"><img src="https://images.tokopedia.net/main.jpg"></button>
+            <button data-testid="PDPImageThumbnail"><img src="https://images.tokopedia.net/back.jpg"></button>
+            <h1 data-testid="lblPDPDetailProductName">Samsung Galaxy A15 8/256GB</h1>
+            <p data-testid="lblPDPDetailProductSoldCounter">Terjual 1 rb+</p>
+            <span data-testid="lblPDPDetailProductRatingNumber">4.9</span>
+            <span data-testid="lblPDPDetailProductRatingCounter">(512 rating)</span>
+            <div data-testid="lblPDPDetailProductPrice">Rp2.599.000</div>
+            <span data-testid="lblPDPDetailDiscountPercentage">13%</span>
+            <span data-testid="lblPDPDetailOriginalPrice">Rp2.999.000</span>
+            <div data-testid="pdpVariantContainer">
+                <button>Biru</button><button>Hitam</button>
+            </div>
+            <p data-testid="stock-label">Stok Total: Sisa 7</p>
+            <ul data-testid="lblPDPInfoProduk">
+                <li>Kondisi: Baru</li>
+                <li>Berat Satuan: 500 g</li>
+            </ul>
+            <div data-testid="lblPDPDescriptionProduk">Garansi resmi SEIN.<br>Segel pabrik.</div>
+            <a data-testid="llbPDPFooterShopName"><h2>Samsung Official Store</h2></a>
+            <img data-testid="pdpShopBadgeOS" src="https://images.tokopedia.net/badge.png">
+        "#;
+
+        let detail = TokopediaParser
+            .parse_product_page(html, "https://www.tokopedia.com/samsung/galaxy-a15")
+            .unwrap();
+
+        assert_eq!(detail.name, "Samsung Galaxy A15 8/256GB");
+        assert_eq!(detail.price, "Rp2.599.000");
+        assert_eq!(detail.original_price_idr, Some(2_999_000));
+        assert_eq!(detail.discount_percent, Some(13));
+        assert_eq!(detail.description, "Garansi resmi SEIN.\nSegel pabrik.");
+        assert_eq!(detail.images, vec!["https://images.tokopedia.net/main.jpg", "https://images.tokopedia.net/back.jpg"]);
+        assert_eq!(detail.variants.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(), vec!["Biru", "Hitam"]);
+        assert_eq!(detail.stock, Some(7));
+        assert_eq!(detail.weight_grams, Some(500));
+        assert_eq!(detail.shop_name.as_deref(), Some("Samsung Official Store"));
+        assert_eq!(detail.shop_badge.as_deref(), Some("Official Store"));
+        assert_eq!(detail.categories, vec!["Handphone & Tablet", "Handphone"]);
+        assert_eq!(detail.rating, Some(4.9));
+        assert_eq!(detail.review_count, Some(512));
+        assert_eq!(detail.sold_min, Some(1_000));
+        assert_eq!(detail.source, "tokopedia");
+    }
+}
@@ -0,0 +1,140 @@
+pub mod sqlite_watch_store;
+#[cfg(feature = "postgres")]
+pub mod postgres_watch_store;
+pub mod watch_controller;
+pub mod watch_dto;
+pub mod watch_model;
+pub mod watch_scheduler;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use axum::{routing::{delete, get}, Router};
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use std::sync::Arc;
+use url::Url;
+
+use crate::alert::alert_model::AlertRule;
+use crate::config::{WatchSettings, WatchStoreKind};
+use crate::state::AppState;
+use crate::watch::watch_model::{PriceObservation, Watch};
+
+pub use sqlite_watch_store::SqliteWatchStore;
+
+/// Persistent store of watches, the observations they record and the alert rules run on them
+#[async_trait]
+pub trait WatchStore: Send + Sync {
+    /// Short name used in logs, e.g. `sqlite`
+    fn name(&self) -> &'static str;
+
+    /// Insert or replace a watch
+    async fn save_watch(&self, watch: &Watch) -> Result<()>;
+
+    /// Store the outcome of a run (`last_run_at`, `next_run_at`, `last_error`)
+    ///
+    /// Unlike [`WatchStore::save_watch`] it never brings back a watch deleted
+    /// while it ran; `false` when the watch no longer exists.
+    async fn save_run(&self, watch: &Watch) -> Result<bool>;
+
+    /// Every watch, oldest first
+    async fn watches(&self) -> Result<Vec<Watch>>;
+
+    /// Remove a watch, keeping the history it recorded; `false` when it does not exist
+    async fn delete_watch(&self, id: &str) -> Result<bool>;
+
+    /// Append observations to their products' history
+    async fn record(&self, observations: &[PriceObservation]) -> Result<()>;
+
+    /// The latest `limit` observations of a product (see [`product_key`]) since
+    /// `since`, oldest first
+    async fn history(&self, product_key: &str, since: Option<u64>, limit: usize) -> Result<Vec<PriceObservation>>;
+
+    /// Insert or replace an alert rule
+    async fn save_alert(&self, rule: &AlertRule) -> Result<()>;
+
+    /// Every alert rule, oldest first
+    async fn alerts(&self) -> Result<Vec<AlertRule>>;
+
+    /// Remove an alert rule; `false` when it does not exist
+    async fn delete_alert(&self, id: &str) -> Result<bool>;
+}
+
+/// Open the configured store, creating its tables when missing
+pub async fn open(settings: &WatchSettings) -> Result<Arc<dyn WatchStore>> {
+    match settings.store {
+        WatchStoreKind::Sqlite => Ok(Arc::new(SqliteWatchStore::open(&settings.sqlite_path)?)),
+        #[cfg(feature = "postgres")]
+        WatchStoreKind::Postgres => Ok(Arc::new(
+            postgres_watch_store::PostgresWatchStore::connect(&settings.postgres_url).await?,
+        )),
+        #[cfg(not(feature = "postgres"))]
+        WatchStoreKind::Postgres => anyhow::bail!("watch.store = \"postgres\" needs a build with the `postgres` feature"),
+    }
+}
+
+/// Parse a cron schedule: minute, hour, day of month, month and day of week
+pub fn parse_schedule(schedule: &str) -> Result<Cron> {
+    Cron::new(schedule)
+        .parse()
+        .map_err(|e| anyhow!("Invalid cron schedule '{schedule}': {e:?}"))
+}
+
+/// First time after the Unix timestamp `after` (in seconds) matching the schedule, in UTC
+pub fn next_run(schedule: &Cron, after: u64) -> Result<u64> {
+    let after = DateTime::<Utc>::from_timestamp(after as i64, 0).ok_or_else(|| anyhow!("Invalid timestamp {after}"))?;
+    let next = schedule
+        .find_next_occurrence(&after, false)
+        .map_err(|e| anyhow!("Schedule '{}' never runs again: {e:?}", schedule.pattern))?;
+    Ok(next.timestamp() as u64)
+}
+
+/// Identity of a product in the history: its page URL without query string or
+/// fragment, so tracking parameters on search result links don't split its history
+pub fn product_key(url: &str) -> String {
+    match Url::parse(url.trim()) {
+        Ok(mut parsed) => {
+            parsed.set_query(None);
+            parsed.set_fragment(None);
+            parsed.to_string().trim_end_matches('/').to_string()
+        }
+        Err(_) => url.trim().to_string(),
+    }
+}
+
+/// Create router for the watchlist and price history endpoints
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/scraper/watches",
+            get(watch_controller::list_watches_handler).post(watch_controller::create_watch_handler),
+        )
+        .route("/api/scraper/watches/:id", delete(watch_controller::delete_watch_handler))
+        .route("/api/scraper/history", get(watch_controller::history_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_product_key_ignores_tracking_parameters() {
+        assert_eq!(
+            product_key(" https://www.tokopedia.com/shop/iphone-15?extParam=ivf%3Dfalse&src=topads#reviews "),
+            "https://www.tokopedia.com/shop/iphone-15"
+        );
+        assert_eq!(product_key("https://www.blibli.com/p/iphone/ps--ABC/"), "https://www.blibli.com/p/iphone/ps--ABC");
+    }
+
+    #[test]
+    fn test_next_run_follows_schedule() {
+        // 2024-01-01 01:30:00 UTC
+        let now = 1_704_072_600;
+        let every_six_hours = parse_schedule("0 */6 * * *").unwrap();
+        assert_eq!(next_run(&every_six_hours, now).unwrap(), 1_704_088_800); // 06:00
+        let daily = parse_schedule("15 2 * * *").unwrap();
+        assert_eq!(next_run(&daily, now).unwrap(), 1_704_075_300); // 02:15
+
+        assert!(parse_schedule("every hour").is_err());
+        assert!(parse_schedule("0 25 * * *").is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tokio_postgres::{Client, NoTls, Row};
+use tracing::{error, info, instrument};
+
+use crate::alert::alert_model::AlertRule;
+use crate::watch::watch_model::{PriceObservation, Watch, WatchTarget};
+use crate::watch::{product_key, WatchStore};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS watches (
+    id TEXT PRIMARY KEY,
+    target TEXT NOT NULL,
+    schedule TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    last_run_at BIGINT,
+    next_run_at BIGINT,
+    last_error TEXT
+);
+CREATE TABLE IF NOT EXISTS price_observations (
+    id BIGSERIAL PRIMARY KEY,
+    product_key TEXT NOT NULL,
+    product_url TEXT NOT NULL,
+    source TEXT NOT NULL,
+    name TEXT NOT NULL,
+    price_idr BIGINT NOT NULL,
+    original_price_idr BIGINT,
+    discount_percent SMALLINT,
+    sold_min BIGINT,
+    rating REAL,
+    review_count BIGINT,
+    stock BIGINT,
+    watch_id TEXT,
+    observed_at BIGINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS price_observations_product
+    ON price_observations (product_key, observed_at);
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id TEXT PRIMARY KEY,
+    created_at BIGINT NOT NULL,
+    rule TEXT NOT NULL
+);
+";
+
+/// Watches and price history in PostgreSQL, for deployments sharing one database
+///
+/// The client is locked per call, so a transaction never interleaves with other queries.
+pub struct PostgresWatchStore {
+    client: Mutex<Client>,
+}
+
+impl PostgresWatchStore {
+    /// Connect with a libpq-style connection string and create the tables when missing
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .context("Failed to connect to PostgreSQL")?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("PostgreSQL connection closed: {e}");
+            }
+        });
+        client.batch_execute(SCHEMA).await.context("Failed to create PostgreSQL tables")?;
+        info!("Connected to PostgreSQL");
+        Ok(Self { client: Mutex::new(client) })
+    }
+}
+
+fn observation_from_row(row: &Row) -> PriceObservation {
+    PriceObservation {
+        product_url: row.get(0),
+        source: row.get(1),
+        name: row.get(2),
+        price_idr: row.get::<_, i64>(3) as u64,
+        original_price_idr: row.get::<_, Option<i64>>(4).map(|v| v as u64),
+        discount_percent: row.get::<_, Option<i16>>(5).map(|v| v as u8),
+        sold_min: row.get::<_, Option<i64>>(6).map(|v| v as u64),
+        rating: row.get(7),
+        review_count: row.get::<_, Option<i64>>(8).map(|v| v as u64),
+        stock: row.get::<_, Option<i64>>(9).map(|v| v as u64),
+        watch_id: row.get(10),
+        observed_at: row.get::<_, i64>(11) as u64,
+    }
+}
+
+#[async_trait]
+impl WatchStore for PostgresWatchStore {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn save_watch(&self, watch: &Watch) -> Result<()> {
+        let target = serde_json::to_string(&watch.target)?;
+        self.client
+            .lock()
+            .await
+            .execute(
+                "INSERT INTO watches (id, target, schedule, created_at, last_run_at, next_run_at, last_error)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (id) DO UPDATE SET target = $2, schedule = $3, created_at = $4,
+                     last_run_at = $5, next_run_at = $6, last_error = $7",
+                &[
+                    &watch.id,
+                    &target,
+                    &watch.schedule,
+                    &(watch.created_at as i64),
+                    &watch.last_run_at.map(|at| at as i64),
+                    &watch.next_run_at.map(|at| at as i64),
+                    &watch.last_error,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn watches(&self) -> Result<Vec<Watch>> {
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query(
+                "SELECT id, target, schedule, created_at, last_run_at, next_run_at, last_error
+                 FROM watches ORDER BY created_at, id",
+                &[],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let id: String = row.get(0);
+                let target: WatchTarget = serde_json::from_str(row.get(1))
+                    .with_context(|| format!("Stored watch {id} is invalid"))?;
+                Ok(Watch {
+                    id,
+                    target,
+                    schedule: row.get(2),
+                    created_at: row.get::<_, i64>(3) as u64,
+                    last_run_at: row.get::<_, Option<i64>>(4).map(|at| at as u64),
+                    next_run_at: row.get::<_, Option<i64>>(5).map(|at| at as u64),
+                    last_error: row.get(6),
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn save_run(&self, watch: &Watch) -> Result<bool> {
+        let updated = self
+            .client
+            .lock()
+            .await
+            .execute(
+                "UPDATE watches SET last_run_at = $2, next_run_at = $3, last_error = $4 WHERE id = $1",
+                &[
+                    &watch.id,
+                    &watch.last_run_at.map(|at| at as i64),
+                    &watch.next_run_at.map(|at| at as i64),
+                    &watch.last_error,
+                ],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn delete_watch(&self, id: &str) -> Result<bool> {
+        Ok(self.client.lock().await.execute("DELETE FROM watches WHERE id = $1", &[&id]).await? > 0)
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn record(&self, observations: &[PriceObservation]) -> Result<()> {
+        // All or nothing, like the SQLite store, so alerts never compare against a partial run
+        let mut client = self.client.lock().await;
+        let transaction = client.transaction().await?;
+        let insert = transaction
+            .prepare(
+                "INSERT INTO price_observations (product_key, product_url, source, name, price_idr,
+                     original_price_idr, discount_percent, sold_min, rating, review_count, stock, watch_id, observed_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+            )
+            .await?;
+        for observation in observations {
+            transaction
+                .execute(
+                    &insert,
+                    &[
+                        &product_key(&observation.product_url),
+                        &observation.product_url,
+                        &observation.source,
+                        &observation.name,
+                        &(observation.price_idr as i64),
+                        &observation.original_price_idr.map(|v| v as i64),
+                        &observation.discount_percent.map(i16::from),
+                        &observation.sold_min.map(|v| v as i64),
+                        &observation.rating,
+                        &observation.review_count.map(|v| v as i64),
+                        &observation.stock.map(|v| v as i64),
+                        &observation.watch_id,
+                        &(observation.observed_at as i64),
+                    ],
+                )
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn history(&self, product_key: &str, since: Option<u64>, limit: usize) -> Result<Vec<PriceObservation>> {
+        // Newest `limit` rows, returned oldest first
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query(
+                "SELECT * FROM (
+                     SELECT product_url, source, name, price_idr, original_price_idr, discount_percent, sold_min,
+                            rating, review_count, stock, watch_id, observed_at, id
+                     FROM price_observations
+                     WHERE product_key = $1 AND observed_at >= $2
+                     ORDER BY observed_at DESC, id DESC
+                     LIMIT $3
+                 ) latest ORDER BY observed_at, id",
+                &[
+                    &product_key,
+                    &(since.unwrap_or(0) as i64),
+                    &i64::try_from(limit).unwrap_or(i64::MAX),
+                ],
+            )
+            .await?;
+        Ok(rows.iter().map(observation_from_row).collect())
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn save_alert(&self, rule: &AlertRule) -> Result<()> {
+        let json = serde_json::to_string(rule)?;
+        self.client
+            .lock()
+            .await
+            .execute(
+                "INSERT INTO alert_rules (id, created_at, rule) VALUES ($1, $2, $3)
+                 ON CONFLICT (id) DO UPDATE SET created_at = $2, rule = $3",
+                &[&rule.id, &(rule.created_at as i64), &json],
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn alerts(&self) -> Result<Vec<AlertRule>> {
+        let rows = self
+            .client
+            .lock()
+            .await
+            .query("SELECT id, rule FROM alert_rules ORDER BY created_at, id", &[])
+            .await?;
+        rows.iter()
+            .map(|row| {
+                let id: &str = row.get(0);
+                serde_json::from_str(row.get(1)).with_context(|| format!("Stored alert {id} is invalid"))
+            })
+            .collect()
+    }
+
+    #[instrument(name = "postgres", level = "debug", skip_all)]
+    async fn delete_alert(&self, id: &str) -> Result<bool> {
+        Ok(self.client.lock().await.execute("DELETE FROM alert_rules WHERE id = $1", &[&id]).await? > 0)
+    }
+}
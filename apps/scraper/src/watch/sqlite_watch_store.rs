@@ -0,0 +1,361 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rusqlite::{params, types::Type, Connection, Row};
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::debug_span;
+
+use crate::alert::alert_model::AlertRule;
+use crate::watch::watch_model::{PriceObservation, Watch, WatchTarget};
+use crate::watch::{product_key, WatchStore};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS watches (
+    id TEXT PRIMARY KEY,
+    target TEXT NOT NULL,
+    schedule TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    last_run_at INTEGER,
+    next_run_at INTEGER,
+    last_error TEXT
+);
+CREATE TABLE IF NOT EXISTS price_observations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_key TEXT NOT NULL,
+    product_url TEXT NOT NULL,
+    source TEXT NOT NULL,
+    name TEXT NOT NULL,
+    price_idr INTEGER NOT NULL,
+    original_price_idr INTEGER,
+    discount_percent INTEGER,
+    sold_min INTEGER,
+    rating REAL,
+    review_count INTEGER,
+    stock INTEGER,
+    watch_id TEXT,
+    observed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS price_observations_product
+    ON price_observations (product_key, observed_at);
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id TEXT PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    rule TEXT NOT NULL
+);
+";
+
+/// Watches and price history in a SQLite database file
+///
+/// `rusqlite` is synchronous, so every query runs on tokio's blocking thread pool.
+pub struct SqliteWatchStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteWatchStore {
+    /// Open or create the database at `path`
+    pub fn open(path: &str) -> Result<Self> {
+        let connection = Connection::open(path).with_context(|| format!("Failed to open SQLite database {path}"))?;
+        Self::with_connection(connection)
+    }
+
+    /// Database that only lives as long as the store, e.g. for tests
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA).context("Failed to create SQLite tables")?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run `query` on the blocking thread pool
+    async fn run<T, F>(&self, query: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        // Blocking threads don't inherit the caller's span
+        let span = debug_span!("sqlite");
+        tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+            let mut connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+            query(&mut connection)
+        })
+        .await
+        .context("SQLite task panicked")?
+    }
+}
+
+fn watch_from_row(row: &Row) -> rusqlite::Result<Watch> {
+    let target: String = row.get(1)?;
+    let target: WatchTarget = serde_json::from_str(&target)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, Type::Text, Box::new(e)))?;
+    Ok(Watch {
+        id: row.get(0)?,
+        target,
+        schedule: row.get(2)?,
+        created_at: row.get::<_, i64>(3)? as u64,
+        last_run_at: row.get::<_, Option<i64>>(4)?.map(|at| at as u64),
+        next_run_at: row.get::<_, Option<i64>>(5)?.map(|at| at as u64),
+        last_error: row.get(6)?,
+    })
+}
+
+fn observation_from_row(row: &Row) -> rusqlite::Result<PriceObservation> {
+    Ok(PriceObservation {
+        product_url: row.get(0)?,
+        source: row.get(1)?,
+        name: row.get(2)?,
+        price_idr: row.get::<_, i64>(3)? as u64,
+        original_price_idr: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+        discount_percent: row.get::<_, Option<i64>>(5)?.map(|v| v as u8),
+        sold_min: row.get::<_, Option<i64>>(6)?.map(|v| v as u64),
+        rating: row.get::<_, Option<f64>>(7)?.map(|v| v as f32),
+        review_count: row.get::<_, Option<i64>>(8)?.map(|v| v as u64),
+        stock: row.get::<_, Option<i64>>(9)?.map(|v| v as u64),
+        watch_id: row.get(10)?,
+        observed_at: row.get::<_, i64>(11)? as u64,
+    })
+}
+
+#[async_trait]
+impl WatchStore for SqliteWatchStore {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn save_watch(&self, watch: &Watch) -> Result<()> {
+        let watch = watch.clone();
+        let target = serde_json::to_string(&watch.target)?;
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO watches (id, target, schedule, created_at, last_run_at, next_run_at, last_error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    watch.id,
+                    target,
+                    watch.schedule,
+                    watch.created_at as i64,
+                    watch.last_run_at.map(|at| at as i64),
+                    watch.next_run_at.map(|at| at as i64),
+                    watch.last_error,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn save_run(&self, watch: &Watch) -> Result<bool> {
+        let watch = watch.clone();
+        self.run(move |connection| {
+            let updated = connection.execute(
+                "UPDATE watches SET last_run_at = ?2, next_run_at = ?3, last_error = ?4 WHERE id = ?1",
+                params![
+                    watch.id,
+                    watch.last_run_at.map(|at| at as i64),
+                    watch.next_run_at.map(|at| at as i64),
+                    watch.last_error,
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+    }
+
+    async fn watches(&self) -> Result<Vec<Watch>> {
+        self.run(|connection| {
+            let mut statement = connection.prepare(
+                "SELECT id, target, schedule, created_at, last_run_at, next_run_at, last_error
+                 FROM watches ORDER BY created_at, id",
+            )?;
+            let watches = statement.query_map([], watch_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(watches)
+        })
+        .await
+    }
+
+    async fn delete_watch(&self, id: &str) -> Result<bool> {
+        let id = id.to_string();
+        self.run(move |connection| Ok(connection.execute("DELETE FROM watches WHERE id = ?1", [id])? > 0))
+            .await
+    }
+
+    async fn record(&self, observations: &[PriceObservation]) -> Result<()> {
+        let observations = observations.to_vec();
+        self.run(move |connection| {
+            let transaction = connection.transaction()?;
+            {
+                let mut insert = transaction.prepare(
+                    "INSERT INTO price_observations (product_key, product_url, source, name, price_idr,
+                         original_price_idr, discount_percent, sold_min, rating, review_count, stock, watch_id, observed_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                )?;
+                for observation in &observations {
+                    insert.execute(params![
+                        product_key(&observation.product_url),
+                        observation.product_url,
+                        observation.source,
+                        observation.name,
+                        observation.price_idr as i64,
+                        observation.original_price_idr.map(|v| v as i64),
+                        observation.discount_percent.map(i64::from),
+                        observation.sold_min.map(|v| v as i64),
+                        observation.rating.map(f64::from),
+                        observation.review_count.map(|v| v as i64),
+                        observation.stock.map(|v| v as i64),
+                        observation.watch_id,
+                        observation.observed_at as i64,
+                    ])?;
+                }
+            }
+            transaction.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn history(&self, product_key: &str, since: Option<u64>, limit: usize) -> Result<Vec<PriceObservation>> {
+        let product_key = product_key.to_string();
+        self.run(move |connection| {
+            // Newest `limit` rows, returned oldest first
+            let mut statement = connection.prepare(
+                "SELECT * FROM (
+                     SELECT product_url, source, name, price_idr, original_price_idr, discount_percent, sold_min,
+                            rating, review_count, stock, watch_id, observed_at, id
+                     FROM price_observations
+                     WHERE product_key = ?1 AND observed_at >= ?2
+                     ORDER BY observed_at DESC, id DESC
+                     LIMIT ?3
+                 ) ORDER BY observed_at, id",
+            )?;
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+            let observations = statement
+                .query_map(params![product_key, since.unwrap_or(0) as i64, limit], observation_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(observations)
+        })
+        .await
+    }
+
+    async fn save_alert(&self, rule: &AlertRule) -> Result<()> {
+        let (id, created_at) = (rule.id.clone(), rule.created_at as i64);
+        let rule = serde_json::to_string(rule)?;
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO alert_rules (id, created_at, rule) VALUES (?1, ?2, ?3)",
+                params![id, created_at, rule],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn alerts(&self) -> Result<Vec<AlertRule>> {
+        let rules = self
+            .run(|connection| {
+                let mut statement = connection.prepare("SELECT id, rule FROM alert_rules ORDER BY created_at, id")?;
+                let rows = statement
+                    .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(rows)
+            })
+            .await?;
+        rules
+            .iter()
+            .map(|(id, rule)| serde_json::from_str(rule).with_context(|| format!("Stored alert {id} is invalid")))
+            .collect()
+    }
+
+    async fn delete_alert(&self, id: &str) -> Result<bool> {
+        let id = id.to_string();
+        self.run(move |connection| Ok(connection.execute("DELETE FROM alert_rules WHERE id = ?1", [id])? > 0))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(url: &str, price_idr: u64, observed_at: u64) -> PriceObservation {
+        PriceObservation {
+            product_url: url.to_string(),
+            source: "tokopedia".to_string(),
+            name: "iPhone 15".to_string(),
+            price_idr,
+            original_price_idr: None,
+            discount_percent: Some(5),
+            sold_min: Some(100),
+            rating: Some(4.9),
+            review_count: None,
+            stock: None,
+            watch_id: Some("w1".to_string()),
+            observed_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_is_kept_per_product_oldest_first() {
+        let store = SqliteWatchStore::open_in_memory().unwrap();
+        store
+            .record(&[
+                observation("https://www.tokopedia.com/shop/iphone?src=topads", 13_000_000, 300),
+                observation("https://www.tokopedia.com/shop/iphone", 14_000_000, 100),
+                observation("https://www.tokopedia.com/shop/case", 45_000, 200),
+                observation("https://www.tokopedia.com/shop/iphone?utm=x", 13_500_000, 200),
+            ])
+            .await
+            .unwrap();
+
+        let key = product_key("https://www.tokopedia.com/shop/iphone");
+        let history = store.history(&key, None, 10).await.unwrap();
+        let prices: Vec<u64> = history.iter().map(|o| o.price_idr).collect();
+        assert_eq!(prices, [14_000_000, 13_500_000, 13_000_000]);
+        assert_eq!(history[0].rating, Some(4.9));
+        assert_eq!(history[0].discount_percent, Some(5));
+
+        // The most recent ones when limited, and only since the given time
+        let latest: Vec<u64> = store.history(&key, None, 2).await.unwrap().iter().map(|o| o.observed_at).collect();
+        assert_eq!(latest, [200, 300]);
+        assert_eq!(store.history(&key, Some(250), 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_watches_round_trip() {
+        let store = SqliteWatchStore::open_in_memory().unwrap();
+        let mut watch = Watch {
+            id: "w1".to_string(),
+            target: WatchTarget::Product {
+                url: "https://www.blibli.com/p/iphone/ps--ABC".to_string(),
+            },
+            schedule: "0 */6 * * *".to_string(),
+            created_at: 1,
+            last_run_at: None,
+            next_run_at: Some(1),
+            last_error: None,
+        };
+        store.save_watch(&watch).await.unwrap();
+        watch.last_run_at = Some(2);
+        watch.next_run_at = None;
+        watch.last_error = Some("blocked".to_string());
+        assert!(store.save_run(&watch).await.unwrap());
+
+        let watches = store.watches().await.unwrap();
+        assert_eq!(watches.len(), 1);
+        assert_eq!(watches[0].last_run_at, Some(2));
+        assert_eq!(watches[0].next_run_at, None);
+        assert_eq!(watches[0].last_error.as_deref(), Some("blocked"));
+        assert!(matches!(&watches[0].target, WatchTarget::Product { url } if url.ends_with("ps--ABC")));
+
+        assert!(store.delete_watch("w1").await.unwrap());
+        assert!(!store.delete_watch("w1").await.unwrap());
+        assert!(store.watches().await.unwrap().is_empty());
+
+        // A run finishing after the watch was deleted does not bring it back
+        assert!(!store.save_run(&watch).await.unwrap());
+        assert!(store.watches().await.unwrap().is_empty());
+    }
+}
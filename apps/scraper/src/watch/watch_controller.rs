@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use tracing::info;
+
+use crate::cache::unix_now;
+use crate::config::settings;
+use crate::error::{ApiResult, ScraperError};
+use crate::marketplace::marketplace_controller::ApiResponseError;
+use crate::marketplace::marketplace_dto::{ApiResponse, SearchRequest};
+use crate::state::AppState;
+use crate::watch::watch_dto::{CreateWatchRequest, HistoryQuery, HistoryResult, PricePoint, PriceTrend};
+use crate::watch::watch_model::{Watch, WatchTarget};
+use crate::watch::{parse_schedule, product_key};
+
+/// HTTP handler adding a search or product to the watchlist
+#[utoipa::path(
+    post,
+    path = "/api/scraper/watches",
+    request_body = CreateWatchRequest,
+    responses(
+        (status = 201, description = "Watch created, its first run is due right away", body = ApiResponse<Watch>),
+        (status = 400, description = "Unknown marketplace, invalid limit or filters, unsupported product URL or invalid schedule", body = inline(ApiResponseError)),
+        (status = 500, description = "Watch could not be stored", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn create_watch_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CreateWatchRequest>,
+) -> ApiResult<Watch> {
+    let schedule = body.schedule.unwrap_or_else(|| settings().watch.default_schedule.clone());
+    info!("Received watch request: {:?}, schedule='{schedule}'", body.target);
+
+    validate(&state, &body.target, &schedule).map_err(ScraperError::InvalidRequest)?;
+
+    let now = unix_now();
+    let watch = Watch {
+        id: Uuid::new_v4().to_string(),
+        target: body.target,
+        schedule,
+        created_at: now,
+        last_run_at: None,
+        next_run_at: Some(now),
+        last_error: None,
+    };
+    state
+        .watches
+        .save_watch(&watch)
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to store watch: {e}")))?;
+    info!("Watch {} created", watch.id);
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(watch, 1))))
+}
+
+/// Reject watches that could only fail, before they are stored
+fn validate(state: &AppState, target: &WatchTarget, schedule: &str) -> Result<(), String> {
+    match target {
+        WatchTarget::Search(params) => {
+            state
+                .marketplaces
+                .resolve(params.sources.as_deref())
+                .map_err(|e| e.to_string())?;
+            SearchRequest::from(params.clone())
+                .validate()
+                .map_err(|e| e.to_string())?;
+        }
+        WatchTarget::Product { url } => {
+            state.marketplaces.find_by_url(url).map_err(|e| e.to_string())?;
+        }
+    }
+    parse_schedule(schedule).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// HTTP handler listing every watch
+#[utoipa::path(
+    get,
+    path = "/api/scraper/watches",
+    responses(
+        (status = 200, description = "Every watch, oldest first", body = ApiResponse<Vec<Watch>>),
+        (status = 500, description = "Watch store error", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn list_watches_handler(State(state): State<AppState>) -> ApiResult<Vec<Watch>> {
+    let watches = state
+        .watches
+        .watches()
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to read watches: {e}")))?;
+    let count = watches.len();
+    Ok((StatusCode::OK, Json(ApiResponse::success(watches, count))))
+}
+
+/// HTTP handler removing a watch; the history it recorded is kept
+#[utoipa::path(
+    delete,
+    path = "/api/scraper/watches/{id}",
+    params(("id" = String, Path, description = "Watch identifier")),
+    responses(
+        (status = 200, description = "Watch removed", body = ApiResponse<String>),
+        (status = 404, description = "Unknown watch", body = inline(ApiResponseError)),
+        (status = 500, description = "Watch store error", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn delete_watch_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<String> {
+    match state.watches.delete_watch(&id).await {
+        Ok(true) => {
+            info!("Watch {id} removed");
+            Ok((StatusCode::OK, Json(ApiResponse::success(id, 1))))
+        }
+        Ok(false) => Err(ScraperError::NotFound(format!("Unknown watch '{id}'"))),
+        Err(e) => Err(ScraperError::Internal(format!("Failed to remove watch {id}: {e}"))),
+    }
+}
+
+/// HTTP handler returning a product's price and sold-count history
+#[utoipa::path(
+    get,
+    path = "/api/scraper/history",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Observations of the product, oldest first, with the trend over them", body = ApiResponse<HistoryResult>),
+        (status = 400, description = "Missing product URL", body = inline(ApiResponseError)),
+        (status = 404, description = "No observations of the product", body = inline(ApiResponseError)),
+        (status = 500, description = "Watch store error", body = inline(ApiResponseError))
+    ),
+    tag = "watches"
+)]
+pub async fn history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<HistoryQuery>,
+) -> ApiResult<HistoryResult> {
+    if params.product.trim().is_empty() {
+        return Err(ScraperError::InvalidRequest("product must be a product page URL".to_string()));
+    }
+
+    let product = product_key(&params.product);
+    let observations = state
+        .watches
+        .history(&product, params.since, params.limit.unwrap_or(1000))
+        .await
+        .map_err(|e| ScraperError::Internal(format!("Failed to read history of {product}: {e}")))?;
+
+    let points: Vec<PricePoint> = observations.iter().map(PricePoint::from).collect();
+    let (Some(latest), Some(trend)) = (observations.last(), PriceTrend::from_points(&points)) else {
+        return Err(ScraperError::NotFound(format!("No price history for {product}")));
+    };
+
+    let count = points.len();
+    let result = HistoryResult {
+        name: latest.name.clone(),
+        source: latest.source.clone(),
+        product,
+        points,
+        trend,
+    };
+    Ok((StatusCode::OK, Json(ApiResponse::success(result, count))))
+}
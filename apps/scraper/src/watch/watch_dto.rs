@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::watch::watch_model::{PriceObservation, WatchTarget};
+
+/// Body of `POST /api/scraper/watches`: either `search` or `product`, plus an optional schedule
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWatchRequest {
+    #[serde(flatten)]
+    pub target: WatchTarget,
+    /// Cron schedule in UTC, e.g. "0 */6 * * *" (default: `watch.default_schedule`)
+    pub schedule: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[derive(IntoParams)]
+pub struct HistoryQuery {
+    /// Product page URL; query string and fragment are ignored
+    pub product: String,
+    /// Only observations at or after this Unix timestamp in seconds
+    pub since: Option<u64>,
+    /// Maximum number of observations, the most recent ones (default: 1000)
+    pub limit: Option<usize>,
+}
+
+/// One observation in a product's history
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PricePoint {
+    /// Unix timestamp in seconds
+    pub observed_at: u64,
+    pub price_idr: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_price_idr: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discount_percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold_min: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stock: Option<u64>,
+}
+
+impl From<&PriceObservation> for PricePoint {
+    fn from(observation: &PriceObservation) -> Self {
+        Self {
+            observed_at: observation.observed_at,
+            price_idr: observation.price_idr,
+            original_price_idr: observation.original_price_idr,
+            discount_percent: observation.discount_percent,
+            sold_min: observation.sold_min,
+            rating: observation.rating,
+            review_count: observation.review_count,
+            stock: observation.stock,
+        }
+    }
+}
+
+/// How price and sales moved over the returned history
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PriceTrend {
+    pub first_price_idr: u64,
+    pub last_price_idr: u64,
+    pub min_price_idr: u64,
+    pub max_price_idr: u64,
+    /// Change from the first to the last price in percent, negative when the price dropped
+    pub price_change_percent: f64,
+    /// Growth of the sold count between the first and last observation that have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold_increase: Option<u64>,
+}
+
+impl PriceTrend {
+    /// Trend of points in chronological order, `None` without points
+    pub fn from_points(points: &[PricePoint]) -> Option<Self> {
+        let (first, last) = (points.first()?, points.last()?);
+        let price_change_percent = if first.price_idr == 0 {
+            0.0
+        } else {
+            let change = (last.price_idr as f64 - first.price_idr as f64) / first.price_idr as f64 * 100.0;
+            (change * 100.0).round() / 100.0
+        };
+        let mut sold = points.iter().filter_map(|point| point.sold_min);
+        let sold_increase = match (sold.next(), sold.next_back()) {
+            (Some(first), Some(last)) => Some(last.saturating_sub(first)),
+            _ => None,
+        };
+
+        Some(Self {
+            first_price_idr: first.price_idr,
+            last_price_idr: last.price_idr,
+            min_price_idr: points.iter().map(|point| point.price_idr).min()?,
+            max_price_idr: points.iter().map(|point| point.price_idr).max()?,
+            price_change_percent,
+            sold_increase,
+        })
+    }
+}
+
+/// Price and sold-count history of one product
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistoryResult {
+    /// Product page URL without query string, identifying the product
+    pub product: String,
+    /// Name in the latest observation
+    pub name: String,
+    /// Marketplace id
+    pub source: String,
+    /// Observations, oldest first
+    pub points: Vec<PricePoint>,
+    pub trend: PriceTrend,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(observed_at: u64, price_idr: u64, sold_min: Option<u64>) -> PricePoint {
+        PricePoint {
+            observed_at,
+            price_idr,
+            original_price_idr: None,
+            discount_percent: None,
+            sold_min,
+            rating: None,
+            review_count: None,
+            stock: None,
+        }
+    }
+
+    #[test]
+    fn test_trend_over_points() {
+        let points = [point(1, 200_000, None), point(2, 150_000, Some(100)), point(3, 180_000, Some(250))];
+        let trend = PriceTrend::from_points(&points).unwrap();
+        assert_eq!((trend.first_price_idr, trend.last_price_idr), (200_000, 180_000));
+        assert_eq!((trend.min_price_idr, trend.max_price_idr), (150_000, 200_000));
+        assert_eq!(trend.price_change_percent, -10.0);
+        assert_eq!(trend.sold_increase, Some(150));
+
+        assert!(PriceTrend::from_points(&[]).is_none());
+        assert_eq!(PriceTrend::from_points(&points[..1]).unwrap().sold_increase, None);
+    }
+}
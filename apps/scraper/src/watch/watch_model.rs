@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::marketplace::marketplace_dto::AggregatedSearchQuery;
+use crate::marketplace::marketplace_model::{Product, ProductDetail};
+
+/// What a watch scrapes on every run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchTarget {
+    /// Every product a search returns, with the parameters of `GET /api/scraper/search`
+    Search(AggregatedSearchQuery),
+    /// A single product page
+    Product {
+        /// Product page URL on a supported marketplace
+        url: String,
+    },
+}
+
+/// A search or product scraped again on a schedule, recording price history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Watch {
+    pub id: String,
+    #[serde(flatten)]
+    pub target: WatchTarget,
+    /// Cron schedule in UTC, e.g. "0 */6 * * *"
+    pub schedule: String,
+    /// Unix timestamps in seconds
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_run_at: Option<u64>,
+    /// `None` once the schedule has no next run, the watch is then never due
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_run_at: Option<u64>,
+    /// Why the last run failed, completely or for some marketplaces
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_error: Option<String>,
+}
+
+/// A product's price and popularity at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PriceObservation {
+    /// Product page URL as scraped
+    pub product_url: String,
+    /// Marketplace id, e.g. `tokopedia`
+    pub source: String,
+    pub name: String,
+    pub price_idr: u64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub original_price_idr: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub discount_percent: Option<u8>,
+    /// Lower bound of the number of items sold
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sold_min: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rating: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub review_count: Option<u64>,
+    /// Units in stock, only known from product pages
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stock: Option<u64>,
+    /// Watch that recorded the observation
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub watch_id: Option<String>,
+    /// Unix timestamp in seconds
+    pub observed_at: u64,
+}
+
+impl PriceObservation {
+    /// Observation of a search result listing
+    pub fn from_product(product: &Product, watch_id: &str, observed_at: u64) -> Self {
+        Self {
+            product_url: product.product_url.clone(),
+            source: product.source.clone(),
+            name: product.name.clone(),
+            price_idr: product.price_idr,
+            original_price_idr: product.original_price_idr,
+            discount_percent: product.discount_percent,
+            sold_min: product.sold_min,
+            rating: product.rating,
+            review_count: product.review_count,
+            stock: None,
+            watch_id: Some(watch_id.to_string()),
+            observed_at,
+        }
+    }
+
+    /// Observation of a product page
+    pub fn from_detail(detail: &ProductDetail, watch_id: &str, observed_at: u64) -> Self {
+        Self {
+            product_url: detail.product_url.clone(),
+            source: detail.source.clone(),
+            name: detail.name.clone(),
+            price_idr: detail.price_idr,
+            original_price_idr: detail.original_price_idr,
+            discount_percent: detail.discount_percent,
+            sold_min: detail.sold_min,
+            rating: detail.rating,
+            review_count: detail.review_count,
+            stock: detail.stock,
+            watch_id: Some(watch_id.to_string()),
+            observed_at,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{bail, Result};
+use std::time::Duration;
+use tracing::{error, info, info_span, warn, Instrument};
+
+use crate::alert::alert_service;
+use crate::cache::{unix_now, CachePolicy};
+use crate::config::settings;
+use crate::marketplace::marketplace_dto::SearchRequest;
+use crate::marketplace::marketplace_service::MarketplaceService;
+use crate::state::AppState;
+use crate::watch::watch_model::{PriceObservation, Watch, WatchTarget};
+use crate::watch::{next_run, parse_schedule};
+
+/// Run due watches every `watch.check_interval_secs`, starting right away
+///
+/// Watches run one after the other, so a slow marketplace delays the next
+/// watch rather than piling up concurrent browser sessions.
+pub fn spawn(state: AppState) {
+    let interval = Duration::from_secs(settings().watch.check_interval_secs);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_due(&state).await {
+                warn!("Failed to check watches in {}: {e}", state.watches.name());
+            }
+        }
+    });
+}
+
+/// Run every watch whose next run is due, returning how many ran
+pub async fn run_due(state: &AppState) -> Result<usize> {
+    let now = unix_now();
+    let due: Vec<Watch> = state
+        .watches
+        .watches()
+        .await?
+        .into_iter()
+        .filter(|watch| watch.next_run_at.is_some_and(|next_run_at| next_run_at <= now))
+        .collect();
+
+    for mut watch in due.iter().cloned() {
+        let observed_at = unix_now();
+        let span = info_span!("watch", watch_id = %watch.id);
+        match run_watch(state, &mut watch, observed_at).instrument(span).await {
+            Ok(count) => info!("Watch {} recorded {count} price(s)", watch.id),
+            Err(e) => {
+                error!("Watch {} failed: {e}", watch.id);
+                watch.last_error = Some(e.to_string());
+            }
+        }
+
+        watch.last_run_at = Some(observed_at);
+        match parse_schedule(&watch.schedule).and_then(|schedule| next_run(&schedule, unix_now())) {
+            Ok(next_run_at) => watch.next_run_at = Some(next_run_at),
+            Err(e) => {
+                // Never due again rather than due on every tick
+                warn!("Watch {} will not run again: {e}", watch.id);
+                watch.next_run_at = None;
+                watch.last_error = Some(e.to_string());
+            }
+        }
+        match state.watches.save_run(&watch).await {
+            Ok(true) => {}
+            Ok(false) => info!("Watch {} was deleted while it ran", watch.id),
+            Err(e) => warn!("Failed to save watch {} to {}: {e}", watch.id, state.watches.name()),
+        }
+    }
+    Ok(due.len())
+}
+
+/// Scrape a watch's target bypassing the cache and record what it found
+///
+/// Sets `last_error` when some marketplaces of a search failed; fails when
+/// nothing could be scraped.
+async fn run_watch(state: &AppState, watch: &mut Watch, observed_at: u64) -> Result<usize> {
+    let fresh = CachePolicy { fresh: true, max_age: None };
+    let observations: Vec<PriceObservation> = match &watch.target {
+        WatchTarget::Search(params) => {
+            info!("Running watch {}: search '{}'", watch.id, params.query);
+            let marketplaces = state.marketplaces.resolve(params.sources.as_deref())?;
+            let sources = marketplaces.len();
+            let request = SearchRequest::from(params.clone()).with_cache_policy(fresh);
+            let result = MarketplaceService::search_all(marketplaces, state, &request, None).await;
+
+            let errors = result
+                .errors
+                .iter()
+                .map(|e| format!("{}: {}", e.source, e.error))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if result.errors.len() == sources {
+                bail!("{errors}");
+            }
+            watch.last_error = (!errors.is_empty()).then_some(errors);
+            // A price of 0 means it could not be read, not that the product is free
+            result
+                .products
+                .iter()
+                .filter(|product| product.price_idr > 0)
+                .map(|product| PriceObservation::from_product(product, &watch.id, observed_at))
+                .collect()
+        }
+        WatchTarget::Product { url } => {
+            info!("Running watch {}: product {url}", watch.id);
+            let service = MarketplaceService::new(state.marketplaces.find_by_url(url)?, state);
+            let Some(detail) = service.product_detail(url, &fresh).await? else {
+                bail!("No product found at {url}");
+            };
+            if detail.price_idr == 0 {
+                bail!("No price found at {url}");
+            }
+            watch.last_error = None;
+            vec![PriceObservation::from_detail(&detail, &watch.id, observed_at)]
+        }
+    };
+
+    // Compared with the previous observations, so before recording these
+    match alert_service::check(state, watch, &observations).await {
+        Ok(0) => {}
+        Ok(fired) => info!("Watch {} fired {fired} alert(s)", watch.id),
+        Err(e) => warn!("Failed to check alerts of watch {}: {e}", watch.id),
+    }
+    state.watches.record(&observations).await?;
+    Ok(observations.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketplace::marketplace_dto::AggregatedSearchQuery;
+    use crate::page_source::test_support::search_page_state;
+    use crate::watch::{product_key, SqliteWatchStore};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_due_search_watch_records_prices() {
+        let state = search_page_state().with_watch_store(Arc::new(SqliteWatchStore::open_in_memory().unwrap()));
+
+        let watch = |id: &str, next_run_at| Watch {
+            id: id.to_string(),
+            target: WatchTarget::Search(
+                serde_json::from_value(serde_json::json!({ "query": "iphone", "limit": 2 })).unwrap(),
+            ),
+            schedule: "0 */6 * * *".to_string(),
+            created_at: 0,
+            last_run_at: None,
+            next_run_at,
+            last_error: None,
+        };
+        state.watches.save_watch(&watch("due", Some(0))).await.unwrap();
+        state.watches.save_watch(&watch("later", Some(unix_now() + 3600))).await.unwrap();
+
+        assert_eq!(run_due(&state).await.unwrap(), 1);
+
+        let watches = state.watches.watches().await.unwrap();
+        let due = watches.iter().find(|watch| watch.id == "due").unwrap();
+        assert!(due.last_run_at.is_some());
+        assert!(due.next_run_at.unwrap() > unix_now());
+        assert_eq!(due.last_error, None);
+        assert!(watches.iter().find(|watch| watch.id == "later").unwrap().last_run_at.is_none());
+
+        // The watch cached what it scraped
+        let params: AggregatedSearchQuery = serde_json::from_value(serde_json::json!({ "query": "iphone", "limit": 1 })).unwrap();
+        let marketplaces = state.marketplaces.resolve(None).unwrap();
+        let result = MarketplaceService::search_all(marketplaces, &state, &SearchRequest::from(params), None).await;
+        let history = state
+            .watches
+            .history(&product_key(&result.products[0].product_url), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].watch_id.as_deref(), Some("due"));
+        assert_eq!(history[0].price_idr, result.products[0].price_idr);
+
+        // Not due again until the schedule says so
+        assert_eq!(run_due(&state).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_watch_without_next_run_is_not_due_again() {
+        let state = search_page_state().with_watch_store(Arc::new(SqliteWatchStore::open_in_memory().unwrap()));
+        let watch = Watch {
+            id: "stuck".to_string(),
+            target: WatchTarget::Search(serde_json::from_value(serde_json::json!({ "query": "iphone" })).unwrap()),
+            schedule: "not a schedule".to_string(),
+            created_at: 0,
+            last_run_at: None,
+            next_run_at: Some(0),
+            last_error: None,
+        };
+        state.watches.save_watch(&watch).await.unwrap();
+
+        assert_eq!(run_due(&state).await.unwrap(), 1);
+        let stuck = state.watches.watches().await.unwrap().remove(0);
+        assert_eq!(stuck.next_run_at, None);
+        assert!(stuck.last_error.unwrap().contains("not a schedule"));
+
+        assert_eq!(run_due(&state).await.unwrap(), 0);
+    }
+}